@@ -0,0 +1,881 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+use sysinfo::System;
+
+use crate::{
+    audit::AuditEntry,
+    cpu::CpuUsage,
+    filter::{parse_filter, FilterContext, FilterTerm},
+    proc::Proc,
+    ram::Ram,
+    watchdog::{parse_action, Action, Watchdog, DEFAULT_ACTION_INTERVAL},
+};
+
+/// how many fired and cleared events are kept
+pub const ALERT_HISTORY_CAPACITY: usize = 200;
+
+/// how far back past the threshold a value has to go before a firing alert clears, as a fraction
+/// of the threshold, unless the rule sets `clear` itself
+const DEFAULT_HYSTERESIS: f64 = 0.1;
+
+/// what a rule looks at
+#[derive(Debug, PartialEq)]
+pub enum AlertTarget {
+    /// `proc <filter> cpu|memory`, checked for every process matching the filter on its own
+    Procs {
+        filter: Vec<FilterTerm>,
+        metric: ProcMetric,
+    },
+    /// `mem available|used|free`
+    Memory(MemField),
+    /// `swap used|free`
+    Swap(MemField),
+    /// `cpu overall` or `cpu cpu3`
+    Cpu(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProcMetric {
+    Cpu,
+    Memory,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemField {
+    Available,
+    Used,
+    Free,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+/// a number from the config, either absolute (bytes or percent of a cpu) or a percentage of the
+/// total (memory or swap)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold {
+    pub value: f64,
+    pub percent: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Percent,
+    Bytes,
+}
+
+/// one line of the alerts config, see `parse_rule`
+#[derive(Debug, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub target: AlertTarget,
+    pub comparison: Comparison,
+    pub threshold: Threshold,
+    /// where the value has to get back to for the alert to clear
+    pub clear: Option<Threshold>,
+    /// seconds the threshold has to be crossed for before the alert fires
+    pub for_secs: u64,
+    /// shell command run when the alert fires or clears
+    pub hook: Option<String>,
+    /// what to do to the process when the alert fires, only for `proc` rules
+    pub action: Option<Action>,
+    /// seconds between actions of this rule on the same process
+    pub every: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertState {
+    Fired,
+    Cleared,
+}
+
+#[derive(Clone, Debug)]
+pub struct AlertEvent {
+    /// seconds since the unix epoch
+    pub time: u64,
+    pub rule: String,
+    /// what crossed the threshold, i.e. `postgres (1234)` or `mem available`
+    pub subject: String,
+    pub pid: Option<u32>,
+    pub state: AlertState,
+    pub value: f64,
+    pub unit: Unit,
+}
+
+/// an alert that is firing right now
+#[derive(Clone, Debug)]
+pub struct FiringAlert {
+    pub rule: String,
+    pub subject: String,
+    pub pid: Option<u32>,
+    /// seconds since the unix epoch it fired at
+    pub since: u64,
+    pub value: f64,
+    pub unit: Unit,
+}
+
+/// a value a rule was checked against, `total` is what percent thresholds are taken of
+struct Sample {
+    pid: Option<u32>,
+    subject: String,
+    value: f64,
+    total: f64,
+    unit: Unit,
+}
+
+/// a (rule, pid) that is crossing its threshold, `fired` once it has for long enough
+struct Tracked {
+    since: u64,
+    fired: Option<u64>,
+    subject: String,
+    value: f64,
+    unit: Unit,
+}
+
+/// evaluates the rules every refresh and keeps what is firing along with a history
+#[derive(Default)]
+pub struct AlertEngine {
+    pub rules: Vec<AlertRule>,
+    /// lines of the config that couldn't be parsed
+    pub errors: Vec<String>,
+    /// where the rules were loaded from, `None` when there is no config
+    pub path: Option<PathBuf>,
+    pub history: VecDeque<AlertEvent>,
+    /// runs the `do` actions of rules, configured with `set` lines
+    pub watchdog: Watchdog,
+    tracked: HashMap<(usize, Option<u32>), Tracked>,
+}
+
+impl AlertEngine {
+    pub fn new() -> AlertEngine {
+        AlertEngine::default()
+    }
+
+    /// loads the rules at `path`, a file that can't be read shows up in `errors`
+    pub fn load(path: &Path) -> AlertEngine {
+        let mut engine = AlertEngine::new();
+
+        match fs::read_to_string(path) {
+            Ok(content) => engine = AlertEngine::parse(&content),
+            Err(e) => engine.errors.push(format!("{}: {}", path.display(), e)),
+        }
+        engine.path = Some(path.to_path_buf());
+
+        engine
+    }
+
+    /// the rules in `content` along with its `set` lines, see `parse_rules` and `Watchdog::set`
+    pub fn parse(content: &str) -> AlertEngine {
+        let mut engine = AlertEngine::new();
+        (engine.rules, engine.errors) = parse_rules(content);
+
+        for (idx, line) in content.lines().enumerate() {
+            if let Some(setting) = line.trim().strip_prefix("set ") {
+                if let Err(e) = engine.watchdog.set(setting) {
+                    engine.errors.push(format!("line {}: {}", idx + 1, e));
+                }
+            }
+        }
+
+        engine
+    }
+
+    /// every alert firing right now, longest firing first
+    pub fn firing(&self) -> Vec<FiringAlert> {
+        let mut ret: Vec<FiringAlert> = self
+            .tracked
+            .iter()
+            .filter_map(|((rule, pid), t)| {
+                Some(FiringAlert {
+                    rule: self.rules[*rule].name.clone(),
+                    subject: t.subject.clone(),
+                    pid: *pid,
+                    since: t.fired?,
+                    value: t.value,
+                    unit: t.unit,
+                })
+            })
+            .collect();
+
+        ret.sort_by_key(|a| (a.since, a.rule.clone(), a.subject.clone()));
+        ret
+    }
+
+    /// checks every rule, `processes` has to be every process on the system. Returns what fired
+    /// or cleared this time, which is also added to `history`
+    pub fn evaluate(
+        &mut self,
+        processes: &[Proc],
+        ram: &Ram,
+        cpus: &[CpuUsage],
+        now: u64,
+    ) -> Vec<AlertEvent> {
+        let mut events: Vec<AlertEvent> = Vec::new();
+        let mut seen: HashSet<(usize, Option<u32>)> = HashSet::new();
+
+        for (idx, rule) in self.rules.iter().enumerate() {
+            for sample in samples(&rule.target, processes, ram, cpus) {
+                let key = (idx, sample.pid);
+                seen.insert(key);
+
+                let threshold = absolute(rule.threshold, sample.total);
+                let crossed = match rule.comparison {
+                    Comparison::Above => sample.value > threshold,
+                    Comparison::Below => sample.value < threshold,
+                };
+                let clear = match rule.clear {
+                    Some(clear) => absolute(clear, sample.total),
+                    None => match rule.comparison {
+                        Comparison::Above => threshold * (1.0 - DEFAULT_HYSTERESIS),
+                        Comparison::Below => threshold * (1.0 + DEFAULT_HYSTERESIS),
+                    },
+                };
+                let cleared = match rule.comparison {
+                    Comparison::Above => sample.value < clear,
+                    Comparison::Below => sample.value > clear,
+                };
+
+                let event = |state: AlertState| AlertEvent {
+                    time: now,
+                    rule: rule.name.clone(),
+                    subject: sample.subject.clone(),
+                    pid: sample.pid,
+                    state,
+                    value: sample.value,
+                    unit: sample.unit,
+                };
+
+                match self.tracked.get_mut(&key) {
+                    Some(tracked) if tracked.fired.is_some() => {
+                        if cleared {
+                            self.tracked.remove(&key);
+                            events.push(event(AlertState::Cleared));
+                        } else {
+                            tracked.value = sample.value;
+                        }
+                    }
+                    Some(tracked) if crossed => {
+                        tracked.value = sample.value;
+                        if now.saturating_sub(tracked.since) >= rule.for_secs {
+                            tracked.fired = Some(now);
+                            events.push(event(AlertState::Fired));
+                        }
+                    }
+                    Some(_) => {
+                        self.tracked.remove(&key);
+                    }
+                    None if crossed => {
+                        let fired = (rule.for_secs == 0).then_some(now);
+                        if fired.is_some() {
+                            events.push(event(AlertState::Fired));
+                        }
+                        self.tracked.insert(
+                            key,
+                            Tracked {
+                                since: now,
+                                fired,
+                                subject: sample.subject.clone(),
+                                value: sample.value,
+                                unit: sample.unit,
+                            },
+                        );
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        // processes that exited (or stopped matching the filter) take their alerts with them
+        let gone: Vec<(usize, Option<u32>)> = self
+            .tracked
+            .keys()
+            .filter(|key| !seen.contains(key))
+            .copied()
+            .collect();
+        for key in gone {
+            if let Some(tracked) = self.tracked.remove(&key) {
+                if tracked.fired.is_some() {
+                    events.push(AlertEvent {
+                        time: now,
+                        rule: self.rules[key.0].name.clone(),
+                        subject: tracked.subject,
+                        pid: key.1,
+                        state: AlertState::Cleared,
+                        value: tracked.value,
+                        unit: tracked.unit,
+                    });
+                }
+            }
+        }
+
+        for event in &events {
+            if self.history.len() >= ALERT_HISTORY_CAPACITY {
+                self.history.pop_front();
+            }
+            self.history.push_back(event.clone());
+        }
+
+        events
+    }
+
+    /// runs the action of the rule `event` is for on `proc`, the process that fired it. Returns
+    /// what happened when there was something to do
+    pub fn act(
+        &mut self,
+        event: &AlertEvent,
+        proc: &Proc,
+        sys: &System,
+        actor: &str,
+    ) -> Option<AuditEntry> {
+        if event.state != AlertState::Fired {
+            return None;
+        }
+
+        let rule = self.rules.iter().find(|r| r.name == event.rule)?;
+        let action = rule.action.as_ref()?;
+
+        Some(
+            self.watchdog
+                .act(&rule.name, action, rule.every, proc, sys, actor, event.time),
+        )
+    }
+
+    /// runs the hook of the rule `event` is for in the background, if it has one
+    pub fn run_hook(&self, event: &AlertEvent) {
+        let hook = match self
+            .rules
+            .iter()
+            .find(|r| r.name == event.rule)
+            .and_then(|r| r.hook.as_deref())
+        {
+            Some(hook) => hook,
+            None => return,
+        };
+
+        let state = match event.state {
+            AlertState::Fired => "fired",
+            AlertState::Cleared => "cleared",
+        };
+
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(hook)
+            .env("CHADTOP_ALERT", &event.rule)
+            .env("CHADTOP_STATE", state)
+            .env("CHADTOP_SUBJECT", &event.subject)
+            .env("CHADTOP_VALUE", event.value.to_string())
+            .env(
+                "CHADTOP_PID",
+                event.pid.map_or(String::new(), |p| p.to_string()),
+            )
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        // reaped in the background so slow hooks don't hold up the ui or turn into zombies
+        if let Ok(mut child) = child {
+            thread::spawn(move || child.wait());
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/chadtop/alerts.conf`, falling back to ~/.config. `None` when it doesn't
+/// exist, not having alerts is fine
+pub fn default_alerts_path() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config.join("chadtop").join("alerts.conf")).filter(|path| path.exists())
+}
+
+fn absolute(threshold: Threshold, total: f64) -> f64 {
+    if threshold.percent {
+        threshold.value / 100.0 * total
+    } else {
+        threshold.value
+    }
+}
+
+fn samples(target: &AlertTarget, processes: &[Proc], ram: &Ram, cpus: &[CpuUsage]) -> Vec<Sample> {
+    let memory = |field: MemField, name: &str, total: u64, used: u64, free: u64, available: u64| {
+        let (label, value) = match field {
+            MemField::Available => ("available", available),
+            MemField::Used => ("used", used),
+            MemField::Free => ("free", free),
+        };
+        Sample {
+            pid: None,
+            subject: format!("{} {}", name, label),
+            value: value as f64,
+            total: total as f64,
+            unit: Unit::Bytes,
+        }
+    };
+
+    match target {
+        AlertTarget::Procs { filter, metric } => {
+            // sockets aren't read for rules, so port terms are rejected when parsing
+            let ctx = FilterContext { sockets: &[] };
+            processes
+                .iter()
+                .filter(|p| filter.iter().all(|t| t.matches(p, &ctx)))
+                .map(|p| {
+                    let subject = format!("{} ({})", p.name.as_deref().unwrap_or("n/a"), p.pid);
+                    match metric {
+                        ProcMetric::Cpu => Sample {
+                            pid: Some(p.pid),
+                            subject,
+                            value: p.cpu_usage as f64,
+                            total: 100.0,
+                            unit: Unit::Percent,
+                        },
+                        ProcMetric::Memory => Sample {
+                            pid: Some(p.pid),
+                            subject,
+                            value: p.memory as f64,
+                            total: ram.total as f64,
+                            unit: Unit::Bytes,
+                        },
+                    }
+                })
+                .collect()
+        }
+        AlertTarget::Memory(field) => {
+            vec![memory(
+                *field,
+                "mem",
+                ram.total,
+                ram.used,
+                ram.free,
+                ram.available,
+            )]
+        }
+        // swap has nothing like available memory
+        AlertTarget::Swap(field) => vec![memory(
+            *field,
+            "swap",
+            ram.total_swap,
+            ram.used_swap,
+            ram.free_swap,
+            ram.free_swap,
+        )],
+        AlertTarget::Cpu(name) => cpus
+            .iter()
+            .filter(|c| &c.name == name)
+            .map(|c| Sample {
+                pid: None,
+                subject: format!("cpu {}", c.name),
+                value: c.usage as f64,
+                total: 100.0,
+                unit: Unit::Percent,
+            })
+            .collect(),
+    }
+}
+
+/// parses every rule in `content`, skipping blank lines and `#` comments. Returns the rules and
+/// an error for every line that isn't one
+pub fn parse_rules(content: &str) -> (Vec<AlertRule>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("set ") {
+            continue;
+        }
+
+        // hooks, actions and their rate limits are looked up by name
+        match parse_rule(line) {
+            Ok(rule) if rules.iter().any(|r: &AlertRule| r.name == rule.name) => errors.push(
+                format!("line {}: duplicate rule name `{}`", idx + 1, rule.name),
+            ),
+            Ok(rule) => rules.push(rule),
+            Err(e) => errors.push(format!("line {}: {}", idx + 1, e)),
+        }
+    }
+
+    (rules, errors)
+}
+
+/// parses a rule like
+/// ```text
+/// busy-postgres: proc postgres cpu > 90% for 30s
+/// low-memory: mem available < 1G clear 2G
+/// swapping: swap used > 50% for 1m hook notify-send "$CHADTOP_ALERT $CHADTOP_STATE"
+/// ```
+/// everything after `hook` is run with `sh -c`. `proc` rules can also act on the process with
+/// `do signal TERM`, `do renice 10` or `do run <command>` at most once per `every` (1m by default)
+/// per process
+pub fn parse_rule(line: &str) -> Result<AlertRule, String> {
+    let (name, rest) = line
+        .split_once(':')
+        .ok_or_else(|| String::from("expected `name: condition`"))?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("bad rule name `{}`", name));
+    }
+
+    let (condition, hook) = match rest.split_once(" hook ") {
+        Some((condition, hook)) => (condition, Some(hook.trim().to_string())),
+        None => (rest, None),
+    };
+    let (condition, run) = match condition.split_once(" do run ") {
+        Some((condition, run)) => (condition, Some(parse_action(&format!("run {}", run))?)),
+        None => (condition, None),
+    };
+
+    let tokens: Vec<&str> = condition.split_whitespace().collect();
+    let op_idx = tokens
+        .iter()
+        .position(|t| *t == ">" || *t == "<")
+        .ok_or_else(|| String::from("expected `>` or `<`"))?;
+
+    let target = parse_target(&tokens[..op_idx])?;
+    let comparison = if tokens[op_idx] == ">" {
+        Comparison::Above
+    } else {
+        Comparison::Below
+    };
+    let threshold = tokens
+        .get(op_idx + 1)
+        .ok_or_else(|| String::from("expected a threshold"))
+        .and_then(|t| parse_threshold(t))?;
+
+    let mut rule = AlertRule {
+        name: name.to_string(),
+        target,
+        comparison,
+        threshold,
+        clear: None,
+        for_secs: 0,
+        hook,
+        action: run,
+        every: DEFAULT_ACTION_INTERVAL,
+    };
+
+    let mut rest = tokens[op_idx + 2..].iter();
+    while let Some(keyword) = rest.next() {
+        let value = rest
+            .next()
+            .ok_or_else(|| format!("expected a value after `{}`", keyword))?;
+        match *keyword {
+            "for" => rule.for_secs = parse_duration(value)?,
+            "clear" => rule.clear = Some(parse_threshold(value)?),
+            "every" => rule.every = parse_duration(value)?,
+            "do" => {
+                let arg = rest
+                    .next()
+                    .ok_or_else(|| format!("expected a value after `do {}`", value))?;
+                rule.action = Some(parse_action(&format!("{} {}", value, arg))?);
+            }
+            _ => return Err(format!("unknown keyword `{}`", keyword)),
+        }
+    }
+
+    if rule.action.is_some() && !matches!(rule.target, AlertTarget::Procs { .. }) {
+        return Err(String::from("only proc rules can `do` something"));
+    }
+
+    // a clear on the wrong side of the threshold would clear on every sample it fires on
+    if let Some(clear) = rule.clear {
+        if clear.percent != rule.threshold.percent {
+            return Err(String::from(
+                "`clear` has to be in the same unit as the threshold",
+            ));
+        }
+        let past = match rule.comparison {
+            Comparison::Above => clear.value < rule.threshold.value,
+            Comparison::Below => clear.value > rule.threshold.value,
+        };
+        if !past {
+            return Err(format!(
+                "`clear` has to be {} the threshold",
+                match rule.comparison {
+                    Comparison::Above => "below",
+                    Comparison::Below => "above",
+                }
+            ));
+        }
+    }
+
+    Ok(rule)
+}
+
+fn parse_target(tokens: &[&str]) -> Result<AlertTarget, String> {
+    let mem_field = |field: Option<&&str>| match field {
+        Some(&"available") => Ok(MemField::Available),
+        Some(&"used") => Ok(MemField::Used),
+        Some(&"free") => Ok(MemField::Free),
+        _ => Err(String::from("expected available, used or free")),
+    };
+
+    match tokens {
+        ["proc", filter @ .., metric] if !filter.is_empty() => {
+            let metric = match *metric {
+                "cpu" => ProcMetric::Cpu,
+                "memory" => ProcMetric::Memory,
+                _ => return Err(String::from("expected cpu or memory")),
+            };
+            let filter = parse_filter(&filter.join(" "));
+            if filter.iter().any(|t| t.needs_sockets()) {
+                return Err(String::from("port filters can't be used in rules"));
+            }
+            Ok(AlertTarget::Procs { filter, metric })
+        }
+        ["mem", field] => Ok(AlertTarget::Memory(mem_field(Some(field))?)),
+        ["swap", field] if *field != "available" => Ok(AlertTarget::Swap(mem_field(Some(field))?)),
+        ["cpu", name] => Ok(AlertTarget::Cpu(name.to_string())),
+        _ => Err(String::from(
+            "expected `proc <filter> cpu|memory`, `mem <field>`, `swap <field>` or `cpu <name>`",
+        )),
+    }
+}
+
+/// `90%`, `1.5G`, `512M`, `64K` or a plain number, sizes are powers of 1024
+fn parse_threshold(s: &str) -> Result<Threshold, String> {
+    let err = || format!("bad threshold `{}`", s);
+
+    if let Some(percent) = s.strip_suffix('%') {
+        return Ok(Threshold {
+            value: percent.parse().map_err(|_| err())?,
+            percent: true,
+        });
+    }
+
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&s[..i], 1024.0),
+        Some((i, 'M' | 'm')) => (&s[..i], 1024.0 * 1024.0),
+        Some((i, 'G' | 'g')) => (&s[..i], 1024.0 * 1024.0 * 1024.0),
+        Some((i, 'T' | 't')) => (&s[..i], 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        _ => (s, 1.0),
+    };
+
+    Ok(Threshold {
+        value: number.parse::<f64>().map_err(|_| err())? * multiplier,
+        percent: false,
+    })
+}
+
+/// `30s`, `5m`, `1h` or a plain number of seconds
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let err = || format!("bad duration `{}`", s);
+
+    let (number, multiplier) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 3600),
+        _ => (s, 1),
+    };
+
+    Ok(number.parse::<u64>().map_err(|_| err())? * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::Signal;
+
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+    fn ram(available: u64) -> Ram {
+        Ram {
+            total: 16 * GIB as u64,
+            available,
+            ..Ram::new()
+        }
+    }
+
+    fn proc(pid: u32, name: &str, cpu_usage: f32) -> Proc {
+        Proc {
+            pid,
+            name: Some(String::from(name)),
+            cpu_usage,
+            ..Default::default()
+        }
+    }
+
+    fn engine(config: &str) -> AlertEngine {
+        let (rules, errors) = parse_rules(config);
+        assert!(errors.is_empty(), "{:?}", errors);
+        AlertEngine {
+            rules,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let (rules, errors) = parse_rules(
+            "# comment\n\
+             busy: proc postgres cpu > 90% for 30s\n\
+             low: mem available < 1G clear 2G hook notify-send low memory\n\
+             swapping: swap used > 50%\n\
+             bad: proc port:80 cpu > 5%\n\
+             nope\n",
+        );
+
+        assert_eq!(rules.len(), 3);
+        assert_eq!(rules[0].for_secs, 30);
+        assert_eq!(
+            rules[0].target,
+            AlertTarget::Procs {
+                filter: vec![FilterTerm::NameOrPid(String::from("postgres"))],
+                metric: ProcMetric::Cpu
+            }
+        );
+        assert_eq!(rules[1].threshold.value, GIB);
+        assert_eq!(rules[1].clear.map(|c| c.value), Some(2.0 * GIB));
+        assert_eq!(rules[1].hook.as_deref(), Some("notify-send low memory"));
+        assert_eq!(rules[2].target, AlertTarget::Swap(MemField::Used));
+        assert!(rules[2].threshold.percent);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("line 5"));
+    }
+
+    #[test]
+    fn test_duplicate_rule_names_are_rejected() {
+        let (rules, errors) = parse_rules(
+            "busy: proc postgres cpu > 90% hook echo postgres\n\
+             busy: proc nginx cpu > 90% do signal TERM\n",
+        );
+
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].hook.as_deref(), Some("echo postgres"));
+        assert_eq!(errors, vec!["line 2: duplicate rule name `busy`"]);
+    }
+
+    #[test]
+    fn test_clear_on_wrong_side_is_rejected() {
+        assert_eq!(
+            parse_rule("busy: cpu overall > 90% clear 95%"),
+            Err(String::from("`clear` has to be below the threshold"))
+        );
+        assert_eq!(
+            parse_rule("low: mem available < 1G clear 1G"),
+            Err(String::from("`clear` has to be above the threshold"))
+        );
+        assert_eq!(
+            parse_rule("low: mem available < 1G clear 20%"),
+            Err(String::from(
+                "`clear` has to be in the same unit as the threshold"
+            ))
+        );
+        assert!(parse_rule("busy: cpu overall > 90% clear 80%").is_ok());
+    }
+
+    #[test]
+    fn test_fires_after_duration_and_clears_with_hysteresis() {
+        let mut engine = engine("busy: proc postgres cpu > 90% for 30s");
+        let cpus: Vec<CpuUsage> = Vec::new();
+        let ram = ram(0);
+
+        let hot = [proc(42, "postgres", 95.0), proc(43, "nginx", 99.0)];
+        assert!(engine.evaluate(&hot, &ram, &cpus, 0).is_empty());
+        assert!(engine.evaluate(&hot, &ram, &cpus, 20).is_empty());
+
+        let fired = engine.evaluate(&hot, &ram, &cpus, 30);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].pid, Some(42));
+        assert_eq!(engine.firing().len(), 1);
+
+        // under the threshold but not under it by 10% yet
+        let cooling = [proc(42, "postgres", 85.0)];
+        assert!(engine.evaluate(&cooling, &ram, &cpus, 31).is_empty());
+        assert_eq!(engine.firing()[0].pid, Some(42));
+
+        let cool = [proc(42, "postgres", 50.0)];
+        let cleared = engine.evaluate(&cool, &ram, &cpus, 32);
+        assert_eq!(cleared[0].state, AlertState::Cleared);
+        assert!(engine.firing().is_empty());
+        assert_eq!(engine.history.len(), 2);
+    }
+
+    #[test]
+    fn test_dip_below_threshold_resets_duration() {
+        let mut engine = engine("busy: proc postgres cpu > 90% for 30s");
+        let ram = ram(0);
+
+        engine.evaluate(&[proc(42, "postgres", 95.0)], &ram, &[], 0);
+        engine.evaluate(&[proc(42, "postgres", 10.0)], &ram, &[], 20);
+
+        assert!(engine
+            .evaluate(&[proc(42, "postgres", 95.0)], &ram, &[], 30)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_memory_rule_and_exited_process() {
+        let mut engine = engine("low: mem available < 10%\nbusy: proc postgres cpu > 90%");
+        let procs = [proc(42, "postgres", 95.0)];
+
+        let fired = engine.evaluate(&procs, &ram(GIB as u64), &[], 0);
+        assert_eq!(fired.len(), 2);
+        assert_eq!(fired[0].subject, "mem available");
+
+        // postgres exiting clears its alert
+        let cleared = engine.evaluate(&[], &ram(GIB as u64), &[], 1);
+        assert_eq!(cleared.len(), 1);
+        assert_eq!(cleared[0].subject, "postgres (42)");
+    }
+
+    #[test]
+    fn test_parse_actions_and_settings() {
+        let engine = AlertEngine::parse(
+            "set allow-users ci\n\
+             set dry-run\n\
+             leak: proc chrome memory > 2G do signal TERM every 5m\n\
+             nice: proc make cpu > 90% for 10s do renice 19\n\
+             log: proc java cpu > 99% do run logger {name} {pid} hook true\n\
+             bad: mem used > 90% do signal KILL\n",
+        );
+
+        assert_eq!(engine.watchdog.allow_users, vec![String::from("ci")]);
+        assert!(engine.watchdog.dry_run);
+        assert_eq!(engine.rules[0].action, Some(Action::Signal(Signal::Term)));
+        assert_eq!(engine.rules[0].every, 300);
+        assert_eq!(engine.rules[1].action, Some(Action::Renice(19)));
+        assert_eq!(
+            engine.rules[2].action,
+            Some(Action::Run(String::from("logger {name} {pid}")))
+        );
+        assert_eq!(engine.rules[2].hook.as_deref(), Some("true"));
+        assert_eq!(engine.errors.len(), 1);
+    }
+
+    #[test]
+    fn test_fired_alert_acts_on_process() {
+        let mut engine = AlertEngine::parse("leak: proc chrome memory > 2G do signal TERM");
+        let chrome = Proc {
+            pid: 42,
+            name: Some(String::from("chrome")),
+            user: Some(String::from("ci")),
+            memory: 3 * GIB as u64,
+            ..Default::default()
+        };
+
+        let events = engine.evaluate(std::slice::from_ref(&chrome), &ram(0), &[], 0);
+        let entry = engine.act(&events[0], &chrome, &System::new(), "ci");
+
+        assert_eq!(entry.map(|e| e.result), Some(String::from("dry run")));
+    }
+
+    #[test]
+    fn test_load_missing_config() {
+        let engine = AlertEngine::load(Path::new("/nonexistent/alerts.conf"));
+
+        assert!(engine.rules.is_empty());
+        assert_eq!(engine.errors.len(), 1);
+    }
+}
@@ -0,0 +1,362 @@
+use std::{
+    collections::VecDeque,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+use sysinfo::Users;
+
+use crate::proc::Proc;
+
+/// how many entries are kept in memory, the file keeps everything
+pub const AUDIT_LOG_CAPACITY: usize = 1000;
+
+/// something chadtop did to a process
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditEntry {
+    /// seconds since the unix epoch
+    pub time: u64,
+    /// the user running chadtop
+    pub actor: String,
+    /// what asked for the action, i.e. `watchdog chrome-leak`
+    pub source: String,
+    pub pid: u32,
+    pub name: Option<String>,
+    pub cmd: Option<String>,
+    /// owner of the target process
+    pub user: Option<String>,
+    /// i.e. `signal TERM` or `renice 10`
+    pub action: String,
+    /// `ok`, `dry run`, or what went wrong
+    pub result: String,
+}
+
+impl AuditEntry {
+    /// an entry for `action` on `proc`, `result` still has to be filled in
+    pub fn new(time: u64, actor: &str, source: &str, proc: &Proc, action: String) -> AuditEntry {
+        AuditEntry {
+            time,
+            actor: actor.to_string(),
+            source: source.to_string(),
+            pid: proc.pid,
+            name: proc.name.clone(),
+            cmd: proc.cmd.clone(),
+            user: proc.user.clone(),
+            action,
+            result: String::new(),
+        }
+    }
+
+    /// one line of json, which is what the audit log file is made of
+    pub fn to_json(&self) -> String {
+        let opt = |s: &Option<String>| s.as_deref().map_or(String::from("null"), json_string);
+
+        format!(
+            "{{\"time\":{},\"actor\":{},\"source\":{},\"pid\":{},\"name\":{},\"cmd\":{},\"user\":{},\"action\":{},\"result\":{}}}",
+            self.time,
+            json_string(&self.actor),
+            json_string(&self.source),
+            self.pid,
+            opt(&self.name),
+            opt(&self.cmd),
+            opt(&self.user),
+            json_string(&self.action),
+            json_string(&self.result),
+        )
+    }
+
+    /// parses a line written by `to_json`, `None` for anything else
+    pub fn from_json(line: &str) -> Option<AuditEntry> {
+        let fields = parse_json_object(line)?;
+        let get = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+        let string = |key: &str| match get(key)? {
+            JsonValue::String(s) => Some(s.clone()),
+            _ => None,
+        };
+        let number = |key: &str| match get(key)? {
+            JsonValue::Number(n) => Some(*n),
+            _ => None,
+        };
+
+        Some(AuditEntry {
+            time: number("time")?,
+            actor: string("actor")?,
+            source: string("source")?,
+            pid: number("pid")?.try_into().ok()?,
+            name: string("name"),
+            cmd: string("cmd"),
+            user: string("user"),
+            action: string("action")?,
+            result: string("result")?,
+        })
+    }
+}
+
+/// appends every action to a json lines file and keeps the latest ones around for the ui
+#[derive(Default)]
+pub struct AuditLog {
+    /// `None` when there is nowhere to write to, entries are still kept in memory
+    pub path: Option<PathBuf>,
+    pub entries: VecDeque<AuditEntry>,
+    /// the last error writing the file
+    pub error: Option<String>,
+}
+
+impl AuditLog {
+    pub fn new(path: Option<PathBuf>) -> AuditLog {
+        AuditLog {
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// an audit log writing to `path` with the last `AUDIT_LOG_CAPACITY` entries already in it,
+    /// so the history screen also covers earlier sessions and other users sharing the file
+    pub fn open(path: PathBuf) -> AuditLog {
+        let mut log = AuditLog::new(None);
+
+        if let Ok(content) = fs::read_to_string(&path) {
+            for entry in content.lines().filter_map(AuditEntry::from_json) {
+                log.record(entry);
+            }
+        }
+
+        log.path = Some(path);
+        log
+    }
+
+    pub fn record(&mut self, entry: AuditEntry) {
+        if let Some(path) = &self.path {
+            self.error = append_line(path, &entry.to_json())
+                .err()
+                .map(|e| format!("{}: {}", path.display(), e));
+        }
+
+        if self.entries.len() >= AUDIT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// `$XDG_STATE_HOME/chadtop/audit.log`, falling back to ~/.local/state
+pub fn default_audit_path() -> Option<PathBuf> {
+    let state = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+
+    Some(state.join("chadtop").join("audit.log"))
+}
+
+/// name of the user running chadtop, the uid when it has no name
+pub fn current_user() -> String {
+    let uid = match fs::metadata("/proc/self") {
+        Ok(meta) => meta.uid(),
+        Err(_) => return env::var("USER").unwrap_or(String::from("n/a")),
+    };
+
+    Users::new_with_refreshed_list()
+        .iter()
+        .find(|u| **u.id() == uid)
+        .map_or(uid.to_string(), |u| u.name().to_string())
+}
+
+enum JsonValue {
+    String(String),
+    Number(u64),
+    Null,
+}
+
+/// parses a flat json object with string, unsigned number and null values, which is all
+/// `to_json` writes
+fn parse_json_object(s: &str) -> Option<Vec<(String, JsonValue)>> {
+    let mut chars = s.trim().chars().peekable();
+    let mut fields = Vec::new();
+
+    let skip_ws = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+
+    if chars.next()? != '{' {
+        return None;
+    }
+
+    loop {
+        skip_ws(&mut chars);
+        match chars.peek()? {
+            '}' if fields.is_empty() => return Some(fields),
+            '"' => {}
+            _ => return None,
+        }
+
+        chars.next();
+        let key = parse_json_string(&mut chars)?;
+        skip_ws(&mut chars);
+        if chars.next()? != ':' {
+            return None;
+        }
+        skip_ws(&mut chars);
+
+        let value = match chars.peek()? {
+            '"' => {
+                chars.next();
+                JsonValue::String(parse_json_string(&mut chars)?)
+            }
+            'n' => {
+                let null: String = chars.by_ref().take(4).collect();
+                (null == "null").then_some(JsonValue::Null)?
+            }
+            c if c.is_ascii_digit() => {
+                let mut n = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                    n.push(*c);
+                    chars.next();
+                }
+                JsonValue::Number(n.parse().ok()?)
+            }
+            _ => return None,
+        };
+        fields.push((key, value));
+
+        skip_ws(&mut chars);
+        match chars.next()? {
+            ',' => {}
+            '}' => return Some(fields),
+            _ => return None,
+        }
+    }
+}
+
+/// the rest of a json string after its opening quote
+fn parse_json_string(chars: &mut impl Iterator<Item = char>) -> Option<String> {
+    let mut ret = String::new();
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(ret),
+            '\\' => match chars.next()? {
+                'n' => ret.push('\n'),
+                'r' => ret.push('\r'),
+                't' => ret.push('\t'),
+                'u' => {
+                    let hex: String = chars.take(4).collect();
+                    ret.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => ret.push(c),
+            },
+            c => ret.push(c),
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 2);
+    ret.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\r' => ret.push_str("\\r"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => ret.push_str(&format!("\\u{:04x}", c as u32)),
+            c => ret.push(c),
+        }
+    }
+
+    ret.push('"');
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_entry_to_json() {
+        let proc = Proc {
+            pid: 42,
+            name: Some(String::from("chrome")),
+            cmd: Some(String::from("chrome --flag=\"x\"")),
+            ..Default::default()
+        };
+        let mut entry = AuditEntry::new(10, "ci", "watchdog leak", &proc, "signal TERM".into());
+        entry.result = String::from("ok");
+
+        assert_eq!(
+            entry.to_json(),
+            "{\"time\":10,\"actor\":\"ci\",\"source\":\"watchdog leak\",\"pid\":42,\"name\":\"chrome\",\"cmd\":\"chrome --flag=\\\"x\\\"\",\"user\":null,\"action\":\"signal TERM\",\"result\":\"ok\"}"
+        );
+    }
+
+    #[test]
+    fn test_entry_json_round_trip() {
+        let proc = Proc {
+            pid: 7,
+            name: Some(String::from("nightly \"job\"\n")),
+            user: Some(String::from("backup")),
+            ..Default::default()
+        };
+        let mut entry = AuditEntry::new(99, "alice", "kill", &proc, "signal KILL".into());
+        entry.result = String::from("failed: \\ \u{1}");
+
+        assert_eq!(AuditEntry::from_json(&entry.to_json()), Some(entry));
+        assert_eq!(AuditEntry::from_json("{\"time\":1}"), None);
+        assert_eq!(AuditEntry::from_json("not json"), None);
+    }
+
+    #[test]
+    fn test_open_loads_earlier_entries() {
+        let path = temp_path("audit_open");
+        let proc = Proc {
+            pid: 42,
+            ..Default::default()
+        };
+        let mut log = AuditLog::new(Some(path.clone()));
+        log.record(AuditEntry::new(1, "a", "kill", &proc, "signal KILL".into()));
+
+        let mut reopened = AuditLog::open(path.clone());
+        reopened.record(AuditEntry::new(2, "b", "kill", &proc, "signal KILL".into()));
+        let lines = fs::read_to_string(&path).unwrap().lines().count();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.entries.len(), 2);
+        assert_eq!(reopened.entries[0].actor, "a");
+        assert_eq!(lines, 2);
+    }
+
+    #[test]
+    fn test_record_appends_to_file() {
+        let path = temp_path("audit").join("audit.log");
+        let mut log = AuditLog::new(Some(path.clone()));
+        let proc = Proc {
+            pid: 42,
+            ..Default::default()
+        };
+
+        log.record(AuditEntry::new(1, "a", "kill", &proc, "signal KILL".into()));
+        log.record(AuditEntry::new(2, "a", "kill", &proc, "signal KILL".into()));
+        let content = fs::read_to_string(&path).unwrap();
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert_eq!(log.entries.len(), 2);
+        assert_eq!(log.error, None);
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// what started a process according to its cgroup path
+#[derive(Clone, Debug, PartialEq)]
+pub enum CgroupOwner {
+    /// a systemd service, scope, etc. or the slice a process lives in when it has no unit
+    Unit(String),
+    Container {
+        /// docker, podman, containerd, cri-o or just container when it can't be told
+        runtime: &'static str,
+        id: String,
+    },
+    /// a kubernetes pod, `container` is the id of the container in the pod when known
+    Pod {
+        uid: String,
+        container: Option<String>,
+    },
+}
+
+impl Display for CgroupOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CgroupOwner::Unit(unit) => write!(f, "{}", unit),
+            CgroupOwner::Container { runtime, id } => write!(f, "{}:{}", runtime, short_id(id)),
+            CgroupOwner::Pod {
+                uid,
+                container: Some(id),
+            } => write!(f, "pod:{}/{}", short_id(uid), short_id(id)),
+            CgroupOwner::Pod {
+                uid,
+                container: None,
+            } => write!(f, "pod:{}", short_id(uid)),
+        }
+    }
+}
+
+impl CgroupOwner {
+    /// the container id, for pods that's the id of the container inside of it
+    pub fn container_id(&self) -> Option<&str> {
+        match self {
+            CgroupOwner::Container { id, .. } => Some(id),
+            CgroupOwner::Pod { container, .. } => container.as_deref(),
+            CgroupOwner::Unit(_) => None,
+        }
+    }
+}
+
+/// the cgroup membership of a process
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcCgroup {
+    /// the most specific path out of every hierarchy, see `parse_cgroup`
+    pub path: String,
+    /// path in the unified (v2) hierarchy, `None` on pure v1 systems
+    pub v2_path: Option<String>,
+    pub owner: Option<CgroupOwner>,
+}
+
+/// where the unified (v2) hierarchy is mounted, /sys/fs/cgroup on v2 only systems and
+/// /sys/fs/cgroup/unified on hybrid ones. `None` on pure v1 systems
+pub fn cgroup2_root() -> Option<PathBuf> {
+    cgroup2_root_from(Path::new("/sys/fs/cgroup"))
+}
+
+pub fn cgroup2_root_from(sys_fs_cgroup: &Path) -> Option<PathBuf> {
+    [sys_fs_cgroup.to_path_buf(), sys_fs_cgroup.join("unified")]
+        .into_iter()
+        .find(|root| root.join("cgroup.controllers").exists())
+}
+
+/// whether `path` is `group` itself or any group below it, every path is below `/`
+pub fn in_cgroup(path: &str, group: &str) -> bool {
+    group == "/"
+        || path == group
+        || path
+            .strip_prefix(group)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// reads /proc/<pid>/cgroup
+pub fn read_cgroup(pid: u32) -> Option<ProcCgroup> {
+    read_cgroup_from(&Path::new("/proc").join(pid.to_string()))
+}
+
+pub fn read_cgroup_from(pid_root: &Path) -> Option<ProcCgroup> {
+    fs::read_to_string(pid_root.join("cgroup"))
+        .ok()
+        .and_then(|content| parse_cgroup(&content))
+}
+
+/// parses the `hierarchy-id:controllers:path` lines of /proc/<pid>/cgroup
+///
+/// on hybrid systems the v2 path is often just `/` while the interesting path is in a v1
+/// hierarchy, so the path is the first one that isn't the root out of v2, the systemd named
+/// hierarchy and then every other v1 hierarchy in order
+pub fn parse_cgroup(content: &str) -> Option<ProcCgroup> {
+    let mut v2_path: Option<&str> = None;
+    let mut systemd_path: Option<&str> = None;
+    let mut v1_path: Option<&str> = None;
+
+    for line in content.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (id, controllers, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(id), Some(controllers), Some(path)) => (id, controllers, path),
+            _ => continue,
+        };
+
+        if id == "0" && controllers.is_empty() {
+            v2_path = Some(path);
+        } else if controllers == "name=systemd" {
+            systemd_path = Some(path);
+        } else if v1_path.is_none() && path != "/" {
+            v1_path = Some(path);
+        }
+    }
+
+    let path = [v2_path, systemd_path, v1_path]
+        .into_iter()
+        .flatten()
+        .find(|p| *p != "/")
+        .or(v2_path)
+        .or(systemd_path)?;
+
+    Some(ProcCgroup {
+        path: path.to_string(),
+        v2_path: v2_path.map(str::to_string),
+        owner: parse_owner(path),
+    })
+}
+
+/// works out who owns a cgroup path from the naming conventions of systemd, docker, podman,
+/// containerd, cri-o and the kubelet
+fn parse_owner(path: &str) -> Option<CgroupOwner> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+    let mut pod: Option<String> = None;
+    let mut container: Option<(&'static str, String)> = None;
+
+    for (i, component) in components.iter().enumerate() {
+        if let Some(uid) = pod_uid(component) {
+            pod = Some(uid);
+        }
+
+        let name = component.strip_suffix(".scope").unwrap_or(component);
+        for (prefix, runtime) in [
+            ("docker-", "docker"),
+            ("libpod-", "podman"),
+            ("cri-containerd-", "containerd"),
+            ("containerd-", "containerd"),
+            ("crio-", "cri-o"),
+        ] {
+            if let Some(id) = name.strip_prefix(prefix).filter(|id| is_container_id(id)) {
+                container = Some((runtime, id.to_string()));
+            }
+        }
+
+        // the cgroupfs driver just uses the bare id under a directory named after the runtime
+        if is_container_id(component) {
+            let runtime = match i.checked_sub(1).map(|parent| components[parent]) {
+                Some("docker") => "docker",
+                Some("libpod_parent") => "podman",
+                _ => "container",
+            };
+            container = Some((runtime, component.to_string()));
+        }
+    }
+
+    if let Some(uid) = pod {
+        return Some(CgroupOwner::Pod {
+            uid,
+            container: container.map(|(_, id)| id),
+        });
+    }
+
+    if let Some((runtime, id)) = container {
+        return Some(CgroupOwner::Container { runtime, id });
+    }
+
+    let is_unit = |c: &&&str| {
+        [".service", ".scope", ".socket", ".mount", ".swap", ".timer"]
+            .iter()
+            .any(|suffix| c.ends_with(suffix))
+    };
+
+    components
+        .iter()
+        .rev()
+        .find(is_unit)
+        .or_else(|| components.iter().rev().find(|c| c.ends_with(".slice")))
+        .map(|unit| CgroupOwner::Unit(unit.to_string()))
+}
+
+/// `pod<uid>` with the cgroupfs driver, `kubepods-<qos>-pod<uid>.slice` with dashes in the uid
+/// turned into underscores with the systemd driver
+fn pod_uid(component: &str) -> Option<String> {
+    let name = component.strip_suffix(".slice").unwrap_or(component);
+    let (_, uid) = name.rsplit_once("pod")?;
+
+    let uid = uid.replace('_', "-");
+    let is_uid = uid.len() == 36 && uid.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+
+    is_uid.then_some(uid)
+}
+
+fn is_container_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// the first 12 characters, the same length docker shows ids with
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f4c2a1b9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a";
+    const UID: &str = "0b1c2d3e-4f5a-6b7c-8d9e-0f1a2b3c4d5e";
+
+    fn owner(path: &str) -> Option<CgroupOwner> {
+        parse_cgroup(&format!("0::{}\n", path)).and_then(|c| c.owner)
+    }
+
+    #[test]
+    fn test_systemd_units() {
+        assert_eq!(
+            owner("/system.slice/nginx.service"),
+            Some(CgroupOwner::Unit(String::from("nginx.service")))
+        );
+        assert_eq!(
+            owner("/user.slice/user-1000.slice/user@1000.service/app.slice/foo.service"),
+            Some(CgroupOwner::Unit(String::from("foo.service")))
+        );
+        assert_eq!(
+            owner("/user.slice/user-1000.slice"),
+            Some(CgroupOwner::Unit(String::from("user-1000.slice")))
+        );
+        assert_eq!(owner("/"), None);
+    }
+
+    #[test]
+    fn test_containers() {
+        assert_eq!(
+            owner(&format!("/system.slice/docker-{}.scope", ID)),
+            Some(CgroupOwner::Container {
+                runtime: "docker",
+                id: ID.to_string()
+            })
+        );
+        assert_eq!(
+            owner(&format!("/docker/{}", ID)),
+            Some(CgroupOwner::Container {
+                runtime: "docker",
+                id: ID.to_string()
+            })
+        );
+        assert_eq!(
+            owner(&format!("/machine.slice/libpod-{}.scope/container", ID)).map(|o| o.to_string()),
+            Some(String::from("podman:3f4c2a1b9d8e"))
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_pods() {
+        let systemd = format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod{}.slice/cri-containerd-{}.scope",
+            UID.replace('-', "_"),
+            ID
+        );
+        let cgroupfs = format!("/kubepods/burstable/pod{}/{}", UID, ID);
+
+        for path in [systemd, cgroupfs] {
+            let owner = owner(&path).unwrap();
+            assert_eq!(
+                owner,
+                CgroupOwner::Pod {
+                    uid: UID.to_string(),
+                    container: Some(ID.to_string())
+                }
+            );
+            assert_eq!(owner.container_id(), Some(ID));
+        }
+    }
+
+    #[test]
+    fn test_hybrid_prefers_non_root_path() {
+        let cgroup = parse_cgroup(
+            "9:name=systemd:/system.slice/sshd.service\n4:memory:/system.slice/sshd.service\n0::/\n",
+        )
+        .unwrap();
+
+        assert_eq!(cgroup.path, "/system.slice/sshd.service");
+        assert_eq!(cgroup.v2_path.as_deref(), Some("/"));
+        assert_eq!(
+            cgroup.owner,
+            Some(CgroupOwner::Unit(String::from("sshd.service")))
+        );
+
+        let v1_only = parse_cgroup("4:memory:/foo\n1:cpu:/\n").unwrap();
+        assert_eq!(v1_only.path, "/foo");
+        assert_eq!(v1_only.v2_path, None);
+    }
+}
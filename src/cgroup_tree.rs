@@ -0,0 +1,310 @@
+use std::{collections::HashMap, fs, path::Path, time::Instant};
+
+/// usage and limits of one cgroup v2 group, a limit is `None` when it's `max` or the controller
+/// isn't enabled for the group
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CgroupNode {
+    /// path relative to the cgroup2 mount, the same format as /proc/<pid>/cgroup
+    pub path: String,
+    /// how deep the group is, the root is 0
+    pub depth: usize,
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<u64>,
+    /// total cpu time in microseconds
+    pub cpu_usage_usec: Option<u64>,
+    /// percent of one cpu since the last refresh, can go above 100
+    pub cpu_usage: Option<f32>,
+    /// cpu.max as (quota, period) in microseconds
+    pub cpu_max: Option<(u64, u64)>,
+    /// periods where the group hit its cpu.max quota
+    pub nr_throttled: Option<u64>,
+    pub throttled_usec: Option<u64>,
+    pub pids_current: Option<u64>,
+    pub pids_max: Option<u64>,
+    pub io_read_bytes: Option<u64>,
+    pub io_written_bytes: Option<u64>,
+    /// from cgroup.events, `None` on kernels without the v2 freezer
+    pub frozen: Option<bool>,
+}
+
+impl CgroupNode {
+    /// the last component of the path, `/` for the root
+    pub fn name(&self) -> &str {
+        match self.path.rsplit_once('/') {
+            Some((_, "")) | None => "/",
+            Some((_, name)) => name,
+        }
+    }
+
+    /// cpu.max in cpus, i.e. 1.5 for a quota of 150000 every 100000
+    pub fn cpu_limit(&self) -> Option<f32> {
+        self.cpu_max
+            .filter(|(_, period)| *period > 0)
+            .map(|(quota, period)| quota as f32 / period as f32)
+    }
+}
+
+/// the whole cgroup v2 tree with cpu usage worked out between refreshes
+#[derive(Default)]
+pub struct CgroupTree {
+    pub nodes: Vec<CgroupNode>,
+    /// path -> (cpu usage_usec, when it was read)
+    last: HashMap<String, (u64, Instant)>,
+}
+
+impl CgroupTree {
+    pub fn new() -> CgroupTree {
+        CgroupTree::default()
+    }
+
+    /// rereads every group under `root`, see `read_cgroup_tree_from`
+    pub fn refresh_from(&mut self, root: &Path, now: Instant) {
+        self.nodes = read_cgroup_tree_from(root);
+
+        for node in self.nodes.iter_mut() {
+            if let (Some(usage), Some((last_usage, last_time))) =
+                (node.cpu_usage_usec, self.last.get(&node.path))
+            {
+                let secs = now.duration_since(*last_time).as_secs_f64();
+                if secs > 0.0 {
+                    node.cpu_usage = Some(
+                        (usage.saturating_sub(*last_usage) as f64 / 1e6 / secs * 100.0) as f32,
+                    );
+                }
+            }
+        }
+
+        self.last = self
+            .nodes
+            .iter()
+            .filter_map(|n| n.cpu_usage_usec.map(|u| (n.path.clone(), (u, now))))
+            .collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.last.clear();
+    }
+}
+
+/// walks the cgroup2 hierarchy mounted at `root` depth first, children sorted by name, so every
+/// group comes right after its parent
+pub fn read_cgroup_tree_from(root: &Path) -> Vec<CgroupNode> {
+    let mut ret = Vec::new();
+    if root.join("cgroup.procs").exists() {
+        walk(root, String::from("/"), 0, &mut ret);
+    }
+    ret
+}
+
+fn walk(dir: &Path, path: String, depth: usize, ret: &mut Vec<CgroupNode>) {
+    ret.push(read_node(dir, path.clone(), depth));
+
+    let mut children: Vec<String> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|e| e.file_name().to_str().map(str::to_string))
+            .collect(),
+        Err(_) => return,
+    };
+    children.sort();
+
+    for child in children {
+        let child_path = if path == "/" {
+            format!("/{}", child)
+        } else {
+            format!("{}/{}", path, child)
+        };
+        walk(&dir.join(&child), child_path, depth + 1, ret);
+    }
+}
+
+fn read_node(dir: &Path, path: String, depth: usize) -> CgroupNode {
+    let read = |file: &str| {
+        fs::read_to_string(dir.join(file))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    // `max` means no limit
+    let number = |file: &str| read(file).and_then(|s| s.parse::<u64>().ok());
+
+    let cpu_stat = read("cpu.stat").unwrap_or_default();
+    let cpu_stat_field = |key: &str| {
+        cpu_stat.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|v| v.strip_prefix(' '))
+                .and_then(|v| v.parse::<u64>().ok())
+        })
+    };
+
+    let cpu_max = read("cpu.max").and_then(|s| {
+        let (quota, period) = s.split_once(' ')?;
+        Some((quota.parse().ok()?, period.parse().ok()?))
+    });
+
+    let frozen = read("cgroup.events").and_then(|events| {
+        events
+            .lines()
+            .find_map(|line| line.strip_prefix("frozen "))
+            .map(|v| v == "1")
+    });
+
+    let (io_read_bytes, io_written_bytes) = read("io.stat")
+        .map(|s| parse_io_stat(&s))
+        .unwrap_or((None, None));
+
+    CgroupNode {
+        path,
+        depth,
+        memory_current: number("memory.current"),
+        memory_max: number("memory.max"),
+        cpu_usage_usec: cpu_stat_field("usage_usec"),
+        cpu_usage: None,
+        cpu_max,
+        nr_throttled: cpu_stat_field("nr_throttled"),
+        throttled_usec: cpu_stat_field("throttled_usec"),
+        pids_current: number("pids.current"),
+        pids_max: number("pids.max"),
+        io_read_bytes,
+        io_written_bytes,
+        frozen,
+    }
+}
+
+/// sums `rbytes` and `wbytes` over every device in io.stat
+fn parse_io_stat(content: &str) -> (Option<u64>, Option<u64>) {
+    let mut read: Option<u64> = None;
+    let mut written: Option<u64> = None;
+
+    for field in content.split_whitespace() {
+        match field.split_once('=') {
+            Some(("rbytes", v)) => read = Some(read.unwrap_or(0) + v.parse::<u64>().unwrap_or(0)),
+            Some(("wbytes", v)) => {
+                written = Some(written.unwrap_or(0) + v.parse::<u64>().unwrap_or(0))
+            }
+            _ => {}
+        }
+    }
+
+    (read, written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, write_file};
+    use std::{path::PathBuf, time::Duration};
+
+    fn write(root: &Path, group: &str, file: &str, content: &str) {
+        write_file(&root.join(group).join(file), content);
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        let root = temp_path(&format!("cgroupfs_{}", name));
+        write(&root, "", "cgroup.procs", "1\n");
+        write(&root, "", "cpu.stat", "usage_usec 1000000\n");
+        write(&root, "system.slice", "cgroup.procs", "");
+        write(&root, "system.slice", "memory.current", "4096\n");
+        write(&root, "system.slice", "memory.max", "max\n");
+        write(&root, "system.slice/nginx.service", "cgroup.procs", "42\n");
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "memory.current",
+            "1024\n",
+        );
+        write(&root, "system.slice/nginx.service", "memory.max", "2048\n");
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "cpu.max",
+            "50000 100000\n",
+        );
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "cpu.stat",
+            "usage_usec 500000\nuser_usec 400000\nsystem_usec 100000\nnr_periods 10\nnr_throttled 3\nthrottled_usec 1200\n",
+        );
+        write(&root, "system.slice/nginx.service", "pids.current", "4\n");
+        write(&root, "system.slice/nginx.service", "pids.max", "max\n");
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "io.stat",
+            "8:0 rbytes=100 wbytes=200 rios=1 wios=2\n259:0 rbytes=1 wbytes=2 rios=1 wios=1\n",
+        );
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "cgroup.events",
+            "populated 1\nfrozen 1\n",
+        );
+        write(&root, "init.scope", "cgroup.procs", "1\n");
+        root
+    }
+
+    #[test]
+    fn test_read_cgroup_tree_from_fixture() {
+        let root = fixture("tree");
+
+        let nodes = read_cgroup_tree_from(&root);
+
+        fs::remove_dir_all(&root).unwrap();
+        let paths: Vec<&str> = nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/",
+                "/init.scope",
+                "/system.slice",
+                "/system.slice/nginx.service"
+            ]
+        );
+        assert_eq!(nodes[3].depth, 2);
+        assert_eq!(nodes[3].name(), "nginx.service");
+        assert_eq!(nodes[0].name(), "/");
+
+        let nginx = &nodes[3];
+        assert_eq!(nginx.memory_current, Some(1024));
+        assert_eq!(nginx.memory_max, Some(2048));
+        assert_eq!(nginx.cpu_limit(), Some(0.5));
+        assert_eq!(nginx.nr_throttled, Some(3));
+        assert_eq!(nginx.throttled_usec, Some(1200));
+        assert_eq!(nginx.pids_current, Some(4));
+        assert_eq!(nginx.pids_max, None);
+        assert_eq!(nginx.io_read_bytes, Some(101));
+        assert_eq!(nginx.io_written_bytes, Some(202));
+        assert_eq!(nginx.frozen, Some(true));
+        assert_eq!(nodes[2].memory_max, None);
+        assert_eq!(nodes[2].frozen, None);
+    }
+
+    #[test]
+    fn test_cpu_usage_between_refreshes() {
+        let root = fixture("usage");
+        let mut tree = CgroupTree::new();
+        let start = Instant::now();
+
+        tree.refresh_from(&root, start);
+        assert_eq!(tree.nodes[3].cpu_usage, None);
+
+        // half a second of cpu over two seconds
+        write(
+            &root,
+            "system.slice/nginx.service",
+            "cpu.stat",
+            "usage_usec 1500000\n",
+        );
+        tree.refresh_from(&root, start + Duration::from_secs(2));
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(tree.nodes[3].cpu_usage, Some(50.0));
+    }
+
+    #[test]
+    fn test_missing_root_is_empty() {
+        assert!(read_cgroup_tree_from(Path::new("/nonexistent/cgroup")).is_empty());
+    }
+}
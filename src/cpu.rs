@@ -1,8 +1,15 @@
 use sysinfo::System;
 
+use crate::cpu_time::CpuBreakdown;
+
+#[derive(Clone)]
 pub struct CpuUsage {
     pub name: String,
     pub usage: f32,
+    /// current frequency in MHz, `None` for the overall entry or when it isn't known
+    pub frequency: Option<u64>,
+    /// user/system/iowait/etc. split of the time, filled in by `CpuTimes::apply` on linux
+    pub breakdown: Option<CpuBreakdown>,
 }
 
 /// before calling this function make sure to refresh sys with the cpu values
@@ -12,12 +19,16 @@ pub fn read_cpus(sys: &mut System) -> Vec<CpuUsage> {
     ret.push(CpuUsage {
         name: String::from("overall"),
         usage: sys.global_cpu_usage(),
+        frequency: None,
+        breakdown: None,
     });
 
     for cpu in sys.cpus() {
         ret.push(CpuUsage {
             name: String::from(cpu.name()),
             usage: cpu.cpu_usage(),
+            frequency: Some(cpu.frequency()).filter(|f| *f > 0),
+            breakdown: None,
         });
     }
 
@@ -0,0 +1,119 @@
+use std::{fs, path::Path};
+
+use sysinfo::System;
+
+/// what hardware chadtop is running on, this doesn't change so it's only read once
+#[derive(Default)]
+pub struct CpuDetails {
+    pub brand: Option<String>,
+    pub vendor: Option<String>,
+    /// one entry per logical cpu, empty when /sys/devices/system/cpu isn't available
+    pub topology: Vec<CpuTopology>,
+}
+
+/// where a logical cpu sits, parsed from /sys/devices/system/cpu/cpuN
+pub struct CpuTopology {
+    pub cpu: u32,
+    /// physical_package_id, i.e. the socket
+    pub package: Option<u32>,
+    pub core: Option<u32>,
+    /// the logical cpus sharing this core through smt, in the kernel's list format (i.e. `0,8`)
+    pub siblings: Option<String>,
+    /// MHz
+    pub min_frequency: Option<u64>,
+    /// MHz
+    pub max_frequency: Option<u64>,
+}
+
+/// before calling this make sure sys has its cpu list, brand and vendor come from the first cpu
+pub fn read_cpu_details(sys: &System) -> CpuDetails {
+    let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+    let first = sys.cpus().first();
+
+    CpuDetails {
+        brand: first.and_then(|cpu| non_empty(cpu.brand().trim())),
+        vendor: first.and_then(|cpu| non_empty(cpu.vendor_id())),
+        topology: read_topology_from(Path::new("/sys/devices/system/cpu")),
+    }
+}
+
+/// reads the topology of every `cpuN` directory under `root`, sorted by cpu number
+pub fn read_topology_from(root: &Path) -> Vec<CpuTopology> {
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut ret: Vec<CpuTopology> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let cpu = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse::<u32>()
+                .ok()?;
+            let dir = entry.path();
+            let read = |file: &str| {
+                fs::read_to_string(dir.join(file))
+                    .ok()
+                    .map(|s| s.trim().to_string())
+            };
+            // cpufreq reports kHz
+            let read_mhz = |file: &str| read(file)?.parse::<u64>().ok().map(|khz| khz / 1000);
+
+            Some(CpuTopology {
+                cpu,
+                package: read("topology/physical_package_id").and_then(|s| s.parse().ok()),
+                core: read("topology/core_id").and_then(|s| s.parse().ok()),
+                siblings: read("topology/thread_siblings_list"),
+                min_frequency: read_mhz("cpufreq/cpuinfo_min_freq"),
+                max_frequency: read_mhz("cpufreq/cpuinfo_max_freq"),
+            })
+        })
+        .collect();
+
+    ret.sort_by_key(|t| t.cpu);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, write_file};
+
+    fn write(root: &Path, cpu: u32, file: &str, content: &str) {
+        write_file(&root.join(format!("cpu{}", cpu)).join(file), content);
+    }
+
+    #[test]
+    fn test_read_topology_from_fixture() {
+        let root = temp_path("cpu");
+        for (cpu, core, siblings) in [(0, 0, "0,2"), (1, 1, "1,3"), (2, 0, "0,2"), (10, 1, "1,3")] {
+            write(&root, cpu, "topology/physical_package_id", "0\n");
+            write(&root, cpu, "topology/core_id", &format!("{}\n", core));
+            write(&root, cpu, "topology/thread_siblings_list", siblings);
+        }
+        write(&root, 0, "cpufreq/cpuinfo_min_freq", "800000\n");
+        write(&root, 0, "cpufreq/cpuinfo_max_freq", "4200000\n");
+        // not cpus
+        fs::create_dir_all(root.join("cpufreq")).unwrap();
+        fs::create_dir_all(root.join("cpuidle")).unwrap();
+
+        let topology = read_topology_from(&root);
+
+        fs::remove_dir_all(&root).unwrap();
+        let cpus: Vec<u32> = topology.iter().map(|t| t.cpu).collect();
+        assert_eq!(cpus, vec![0, 1, 2, 10]);
+        assert_eq!(topology[2].core, Some(0));
+        assert_eq!(topology[2].siblings.as_deref(), Some("0,2"));
+        assert_eq!(topology[0].min_frequency, Some(800));
+        assert_eq!(topology[0].max_frequency, Some(4200));
+        assert_eq!(topology[1].max_frequency, None);
+    }
+
+    #[test]
+    fn test_read_topology_missing_root() {
+        assert!(read_topology_from(Path::new("/nonexistent/cpu")).is_empty());
+    }
+}
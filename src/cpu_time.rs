@@ -0,0 +1,184 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::cpu::CpuUsage;
+
+/// how a cpu spent its time between two samples, each field is a percentage of the whole
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuBreakdown {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    /// time a hypervisor gave to another vm while this one wanted to run
+    pub steal: f32,
+}
+
+/// the first 8 counters of a `cpu` line in /proc/stat, in USER_HZ ticks since boot
+type Ticks = [u64; 8];
+
+/// diffs consecutive reads of /proc/stat into a `CpuBreakdown` per cpu
+///
+/// the counters only ever go up so the first sample has nothing to diff against and yields no
+/// breakdowns
+#[derive(Default)]
+pub struct CpuTimes {
+    /// cpu name as sysinfo names it -> counters of the last sample
+    last: HashMap<String, Ticks>,
+    breakdowns: HashMap<String, CpuBreakdown>,
+}
+
+impl CpuTimes {
+    pub fn new() -> CpuTimes {
+        CpuTimes::default()
+    }
+
+    pub fn sample(&mut self) {
+        self.sample_from(Path::new("/proc"));
+    }
+
+    /// same as `sample` but reads from `proc_root` instead of `/proc`
+    pub fn sample_from(&mut self, proc_root: &Path) {
+        let content = match fs::read_to_string(proc_root.join("stat")) {
+            Ok(content) => content,
+            Err(_) => return,
+        };
+
+        let current = parse_stat(&content);
+
+        self.breakdowns = current
+            .iter()
+            .filter_map(|(name, ticks)| {
+                let last = self.last.get(name)?;
+                Some((name.clone(), breakdown(last, ticks)?))
+            })
+            .collect();
+        self.last = current;
+    }
+
+    /// fills in `breakdown` from the last sample
+    pub fn apply(&self, cpus: &mut [CpuUsage]) {
+        for cpu in cpus.iter_mut() {
+            cpu.breakdown = self.breakdowns.get(&cpu.name).copied();
+        }
+    }
+}
+
+/// parses the `cpu` lines of /proc/stat, the aggregate `cpu` line is named "overall" to match
+/// `read_cpus`
+fn parse_stat(content: &str) -> HashMap<String, Ticks> {
+    content
+        .lines()
+        .filter(|line| line.starts_with("cpu"))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = match fields.next()? {
+                "cpu" => String::from("overall"),
+                name => name.to_string(),
+            };
+
+            let mut ticks: Ticks = [0; 8];
+            for tick in ticks.iter_mut() {
+                // steal was only added in 2.6.11, anything older just has zeroes
+                *tick = fields.next().and_then(|f| f.parse().ok()).unwrap_or(0);
+            }
+
+            Some((name, ticks))
+        })
+        .collect()
+}
+
+fn breakdown(last: &Ticks, current: &Ticks) -> Option<CpuBreakdown> {
+    let mut delta: Ticks = [0; 8];
+    for (i, d) in delta.iter_mut().enumerate() {
+        *d = current[i].saturating_sub(last[i]);
+    }
+
+    let total: u64 = delta.iter().sum();
+    if total == 0 {
+        return None;
+    }
+
+    let pct = |ticks: u64| ticks as f32 / total as f32 * 100.0;
+
+    Some(CpuBreakdown {
+        user: pct(delta[0]),
+        nice: pct(delta[1]),
+        system: pct(delta[2]),
+        idle: pct(delta[3]),
+        iowait: pct(delta[4]),
+        irq: pct(delta[5]),
+        softirq: pct(delta[6]),
+        steal: pct(delta[7]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_breakdown_from_two_samples() {
+        let root = temp_path("stat");
+        fs::create_dir_all(&root).unwrap();
+        let mut times = CpuTimes::new();
+
+        fs::write(
+            root.join("stat"),
+            "cpu  100 0 100 800 0 0 0 0 0 0\ncpu0 100 0 100 800 0 0 0 0 0 0\nintr 1 2 3\n",
+        )
+        .unwrap();
+        times.sample_from(&root);
+        assert!(times.breakdowns.is_empty());
+
+        fs::write(
+            root.join("stat"),
+            "cpu  150 0 110 820 10 0 0 10 0 0\ncpu0 150 0 110 820 10 0 0 10 0 0\nintr 1 2 3\n",
+        )
+        .unwrap();
+        times.sample_from(&root);
+
+        fs::remove_dir_all(&root).unwrap();
+        let cpu0 = times.breakdowns["cpu0"];
+        assert_eq!(cpu0.user, 50.0);
+        assert_eq!(cpu0.system, 10.0);
+        assert_eq!(cpu0.idle, 20.0);
+        assert_eq!(cpu0.iowait, 10.0);
+        assert_eq!(cpu0.steal, 10.0);
+        assert!(times.breakdowns.contains_key("overall"));
+    }
+
+    #[test]
+    fn test_apply_matches_cpu_names() {
+        let mut times = CpuTimes::new();
+        times.breakdowns.insert(
+            String::from("cpu1"),
+            CpuBreakdown {
+                user: 100.0,
+                ..Default::default()
+            },
+        );
+        let mut cpus = vec![
+            CpuUsage {
+                name: String::from("cpu0"),
+                usage: 0.0,
+                frequency: None,
+                breakdown: None,
+            },
+            CpuUsage {
+                name: String::from("cpu1"),
+                usage: 100.0,
+                frequency: None,
+                breakdown: None,
+            },
+        ];
+
+        times.apply(&mut cpus);
+
+        assert_eq!(cpus[0].breakdown, None);
+        assert_eq!(cpus[1].breakdown.map(|b| b.user), Some(100.0));
+    }
+}
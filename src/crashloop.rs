@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::events::{EventKind, ProcEvent};
+
+/// an executable + command line that keeps exiting and being started again
+pub struct CrashLoop {
+    pub name: Option<String>,
+    pub exe: Option<String>,
+    pub cmd: Option<String>,
+    /// exits inside the detection window
+    pub exits: usize,
+    /// seconds since the unix epoch of the most recent exit
+    pub last_exit: u64,
+    pub last_pid: u32,
+}
+
+/// what counts as a crash loop, more than `max_exits` exits of the same exe and cmd within
+/// `window` seconds. Exits are only seen between refreshes, so a process that respawns more
+/// than once a second shows up as one exit per refresh at most
+#[derive(Clone, Copy, Debug)]
+pub struct CrashLoopRule {
+    pub max_exits: usize,
+    pub window: u64,
+}
+
+impl Default for CrashLoopRule {
+    fn default() -> Self {
+        CrashLoopRule {
+            max_exits: 3,
+            window: 60,
+        }
+    }
+}
+
+impl CrashLoopRule {
+    /// the shortest window the crash loops screen can be set to, in seconds
+    pub const MIN_WINDOW: u64 = 10;
+
+    pub fn set_max_exits(&mut self, delta: isize) {
+        self.max_exits = self.max_exits.saturating_add_signed(delta).max(1);
+    }
+
+    pub fn set_window(&mut self, delta: i64) {
+        self.window = self
+            .window
+            .saturating_add_signed(delta)
+            .max(Self::MIN_WINDOW);
+    }
+}
+
+/// groups the exits in `events` by (exe, cmd) and returns the groups breaking `rule`, most exits
+/// first
+pub fn detect_crash_loops(
+    events: &VecDeque<ProcEvent>,
+    now: u64,
+    rule: CrashLoopRule,
+) -> Vec<CrashLoop> {
+    let since = now.saturating_sub(rule.window);
+    let mut groups: HashMap<(Option<&str>, Option<&str>), CrashLoop> = HashMap::new();
+
+    for event in events
+        .iter()
+        .filter(|e| e.kind == EventKind::Exit && e.time >= since)
+    {
+        let group = groups
+            .entry((event.exe.as_deref(), event.cmd.as_deref()))
+            .or_insert(CrashLoop {
+                name: None,
+                exe: event.exe.clone(),
+                cmd: event.cmd.clone(),
+                exits: 0,
+                last_exit: 0,
+                last_pid: 0,
+            });
+
+        group.exits += 1;
+        if event.time >= group.last_exit {
+            group.last_exit = event.time;
+            group.last_pid = event.pid;
+            group.name = event.name.clone();
+        }
+    }
+
+    let mut ret: Vec<CrashLoop> = groups
+        .into_values()
+        .filter(|g| g.exits > rule.max_exits)
+        .collect();
+    ret.sort_by(|a, b| b.exits.cmp(&a.exits).then(b.last_exit.cmp(&a.last_exit)));
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit(pid: u32, time: u64, cmd: &str) -> ProcEvent {
+        ProcEvent {
+            kind: EventKind::Exit,
+            time,
+            pid,
+            name: Some(String::from("worker")),
+            cmd: Some(String::from(cmd)),
+            exe: Some(String::from("/usr/bin/worker")),
+            ppid: Some(1),
+            parent_name: Some(String::from("systemd")),
+            lifetime: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_detects_loop_over_threshold() {
+        let events: VecDeque<ProcEvent> = (0..5)
+            .map(|i| exit(100 + i, 1000 + i as u64 * 5, "worker --broken"))
+            .collect();
+
+        let loops = detect_crash_loops(&events, 1030, CrashLoopRule::default());
+
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].exits, 5);
+        assert_eq!(loops[0].last_pid, 104);
+        assert_eq!(loops[0].last_exit, 1020);
+    }
+
+    #[test]
+    fn test_old_exits_and_different_cmds_are_not_grouped() {
+        let mut events: VecDeque<ProcEvent> = VecDeque::new();
+        // outside of the window
+        for i in 0..10 {
+            events.push_back(exit(i, 10 + i as u64, "worker --broken"));
+        }
+        // in the window but all different commands
+        for i in 0..10 {
+            events.push_back(exit(100 + i, 1000, &format!("worker --job {}", i)));
+        }
+
+        let loops = detect_crash_loops(&events, 1010, CrashLoopRule::default());
+
+        assert!(loops.is_empty());
+    }
+
+    #[test]
+    fn test_rule_adjusts_within_bounds() {
+        let mut rule = CrashLoopRule::default();
+
+        rule.set_max_exits(-5);
+        rule.set_window(-100);
+        assert_eq!(rule.max_exits, 1);
+        assert_eq!(rule.window, CrashLoopRule::MIN_WINDOW);
+
+        rule.set_max_exits(2);
+        rule.set_window(20);
+        assert_eq!(rule.max_exits, 3);
+        assert_eq!(rule.window, 30);
+    }
+}
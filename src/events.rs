@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::proc::Proc;
+
+/// how many events are kept before the oldest ones are dropped
+pub const EVENT_LOG_CAPACITY: usize = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EventKind {
+    Spawn,
+    Exit,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProcEvent {
+    pub kind: EventKind,
+    /// seconds since the unix epoch the event was noticed at
+    pub time: u64,
+    pub pid: u32,
+    pub name: Option<String>,
+    pub cmd: Option<String>,
+    pub exe: Option<String>,
+    pub ppid: Option<u32>,
+    pub parent_name: Option<String>,
+    /// how long the process lived, only set for exits
+    pub lifetime: Option<u64>,
+}
+
+/// the parts of a `Proc` we need to remember to describe it once it has exited
+struct Seen {
+    name: Option<String>,
+    cmd: Option<String>,
+    exe: Option<String>,
+    ppid: Option<u32>,
+    parent_name: Option<String>,
+}
+
+/// diffs consecutive process snapshots and keeps the last `EVENT_LOG_CAPACITY` spawns and exits
+///
+/// processes are keyed by (pid, start time) so a reused pid shows up as an exit and a spawn.
+/// Processes that start and exit between two refreshes are never seen and so never logged
+pub struct EventLog {
+    pub events: VecDeque<ProcEvent>,
+    seen: HashMap<(u32, u64), Seen>,
+    initialized: bool,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventLog {
+    pub fn new() -> EventLog {
+        EventLog {
+            events: VecDeque::with_capacity(EVENT_LOG_CAPACITY),
+            seen: HashMap::new(),
+            initialized: false,
+        }
+    }
+
+    /// compares `procs` against the last snapshot and logs what changed, returns how many
+    /// events were added
+    ///
+    /// `procs` should be every process on the system, not just the filtered ones. The first
+    /// snapshot only records what's running without logging every process as a spawn
+    pub fn update(&mut self, procs: &[Proc], now: u64) -> usize {
+        let names: HashMap<u32, &Option<String>> = procs.iter().map(|p| (p.pid, &p.name)).collect();
+        let alive: HashSet<(u32, u64)> = procs.iter().map(|p| (p.pid, p.start_time)).collect();
+
+        let mut added = 0;
+
+        let mut exited: Vec<(u32, u64)> = self
+            .seen
+            .keys()
+            .filter(|key| !alive.contains(key))
+            .copied()
+            .collect();
+        // the map has no order, oldest first keeps the log (and what a full log drops) stable
+        exited.sort_by_key(|&(pid, start_time)| (start_time, pid));
+
+        for key in exited {
+            if let Some(seen) = self.seen.remove(&key) {
+                self.push(ProcEvent {
+                    kind: EventKind::Exit,
+                    time: now,
+                    pid: key.0,
+                    name: seen.name,
+                    cmd: seen.cmd,
+                    exe: seen.exe,
+                    ppid: seen.ppid,
+                    parent_name: seen.parent_name,
+                    lifetime: Some(now.saturating_sub(key.1)),
+                });
+                added += 1;
+            }
+        }
+
+        for proc in procs {
+            let key = (proc.pid, proc.start_time);
+            if self.seen.contains_key(&key) {
+                continue;
+            }
+
+            let parent_name = proc
+                .ppid
+                .and_then(|ppid| names.get(&ppid))
+                .and_then(|name| (*name).clone());
+
+            if self.initialized {
+                self.push(ProcEvent {
+                    kind: EventKind::Spawn,
+                    time: now,
+                    pid: proc.pid,
+                    name: proc.name.clone(),
+                    cmd: proc.cmd.clone(),
+                    exe: proc.exe.clone(),
+                    ppid: proc.ppid,
+                    parent_name: parent_name.clone(),
+                    lifetime: None,
+                });
+                added += 1;
+            }
+
+            self.seen.insert(
+                key,
+                Seen {
+                    name: proc.name.clone(),
+                    cmd: proc.cmd.clone(),
+                    exe: proc.exe.clone(),
+                    ppid: proc.ppid,
+                    parent_name,
+                },
+            );
+        }
+
+        self.initialized = true;
+        added
+    }
+
+    fn push(&mut self, event: ProcEvent) {
+        if self.events.len() >= EVENT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: u32, start_time: u64) -> Proc {
+        Proc {
+            pid,
+            ppid: Some(1),
+            name: Some(format!("proc{}", pid)),
+            start_time,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_first_snapshot_logs_nothing() {
+        let mut log = EventLog::new();
+
+        assert_eq!(log.update(&[proc(1, 0), proc(2, 10)], 100), 0);
+        assert!(log.events.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_and_exit_are_logged() {
+        let mut log = EventLog::new();
+        log.update(&[proc(1, 0), proc(2, 10)], 100);
+
+        log.update(&[proc(1, 0), proc(3, 101)], 102);
+
+        assert_eq!(log.events.len(), 2);
+        let exit = &log.events[0];
+        assert_eq!(exit.kind, EventKind::Exit);
+        assert_eq!(exit.pid, 2);
+        assert_eq!(exit.lifetime, Some(92));
+        assert_eq!(exit.parent_name.as_deref(), Some("proc1"));
+
+        let spawn = &log.events[1];
+        assert_eq!(spawn.kind, EventKind::Spawn);
+        assert_eq!(spawn.pid, 3);
+    }
+
+    #[test]
+    fn test_exits_are_logged_oldest_first() {
+        let mut log = EventLog::new();
+        let procs: Vec<Proc> = (2..12).map(|pid| proc(pid, 100 - pid as u64)).collect();
+        log.update(&procs, 100);
+
+        log.update(&[], 101);
+
+        let pids: Vec<u32> = log.events.iter().map(|e| e.pid).collect();
+        assert_eq!(pids, (2..12).rev().collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_reused_pid_is_exit_and_spawn() {
+        let mut log = EventLog::new();
+        log.update(&[proc(5, 10)], 100);
+
+        log.update(&[proc(5, 150)], 151);
+
+        let kinds: Vec<EventKind> = log.events.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![EventKind::Exit, EventKind::Spawn]);
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut log = EventLog::new();
+        log.update(&[], 0);
+
+        for i in 0..(EVENT_LOG_CAPACITY as u32 + 10) {
+            log.update(&[proc(i + 2, i as u64)], i as u64);
+        }
+
+        assert_eq!(log.events.len(), EVENT_LOG_CAPACITY);
+    }
+}
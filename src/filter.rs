@@ -0,0 +1,243 @@
+use crate::{
+    cgroup::{in_cgroup, CgroupOwner},
+    namespace::NsKind,
+    net::Socket,
+    proc::{Proc, RunState},
+};
+
+/// a single whitespace separated piece of the filter, a process has to match every term to be
+/// shown
+#[derive(Debug, PartialEq)]
+pub enum FilterTerm {
+    /// plain text, matches the start of the process name or pid
+    NameOrPid(String),
+    /// `port:8080`, matches processes holding a socket bound to that local port
+    Port(u16),
+    /// `state:zombie` or `state:Z`, matches processes in that run state
+    State(RunState),
+    /// `user:alice`, matches processes owned by exactly that user. `user:n/a` matches processes
+    /// without a known owner, which is how the users screen lists them
+    User(String),
+    /// `unit:nginx.service` or `unit:nginx`, matches the start of the systemd unit or slice
+    Unit(String),
+    /// `container:3f4c2a`, matches the start of the container id, including containers in pods
+    Container(String),
+    /// `cgroup:/system.slice`, matches processes in that cgroup v2 group or any group below it
+    Cgroup(String),
+    /// `ns:net:4026531840`, matches processes in the namespace with that inode
+    Namespace(NsKind, u64),
+    /// `nspid:1`, matches processes that have that pid inside of their own pid namespace
+    NsPid(u32),
+}
+
+/// everything outside of `Proc` that a filter term might need to look at
+pub struct FilterContext<'a> {
+    pub sockets: &'a [Socket],
+}
+
+/// splits the filter string into terms
+///
+/// keyed terms that aren't finished being typed yet (i.e. `port:` or `port:80a`) are ignored so
+/// the process list doesn't go blank while typing
+pub fn parse_filter(filter: &str) -> Vec<FilterTerm> {
+    filter
+        .split_whitespace()
+        .filter_map(|term| match term.split_once(':') {
+            Some(("port", value)) => value.parse::<u16>().ok().map(FilterTerm::Port),
+            Some(("state", value)) => RunState::parse(value).map(FilterTerm::State),
+            Some(("user", ""))
+            | Some(("unit", ""))
+            | Some(("container", ""))
+            | Some(("cgroup", "")) => None,
+            Some(("user", value)) => Some(FilterTerm::User(value.to_string())),
+            Some(("unit", value)) => Some(FilterTerm::Unit(value.to_string())),
+            Some(("container", value)) => Some(FilterTerm::Container(value.to_string())),
+            Some(("cgroup", value)) => Some(FilterTerm::Cgroup(value.to_string())),
+            Some(("ns", value)) => value.split_once(':').and_then(|(kind, inode)| {
+                Some(FilterTerm::Namespace(
+                    NsKind::parse(kind)?,
+                    inode.parse().ok()?,
+                ))
+            }),
+            Some(("nspid", value)) => value.parse::<u32>().ok().map(FilterTerm::NsPid),
+            _ => Some(FilterTerm::NameOrPid(term.to_string())),
+        })
+        .collect()
+}
+
+impl FilterTerm {
+    pub fn matches(&self, proc: &Proc, ctx: &FilterContext) -> bool {
+        match self {
+            FilterTerm::NameOrPid(text) => {
+                proc.name
+                    .as_deref()
+                    .unwrap_or("")
+                    .starts_with(text.as_str())
+                    || proc.pid.to_string().starts_with(text.as_str())
+            }
+            FilterTerm::Port(port) => ctx
+                .sockets
+                .iter()
+                .any(|s| s.pid == Some(proc.pid) && s.local_port == Some(*port)),
+            FilterTerm::State(state) => proc.state == *state,
+            FilterTerm::User(user) => proc.user.as_deref().unwrap_or("n/a") == user,
+            FilterTerm::Unit(unit) => matches!(
+                proc.cgroup.as_ref().and_then(|c| c.owner.as_ref()),
+                Some(CgroupOwner::Unit(name)) if name.starts_with(unit.as_str())
+            ),
+            FilterTerm::Container(id) => proc
+                .cgroup
+                .as_ref()
+                .and_then(|c| c.owner.as_ref())
+                .and_then(|o| o.container_id())
+                .is_some_and(|container| container.starts_with(id.as_str())),
+            FilterTerm::Cgroup(group) => proc
+                .cgroup
+                .as_ref()
+                .and_then(|c| c.v2_path.as_deref())
+                .is_some_and(|path| in_cgroup(path, group)),
+            FilterTerm::Namespace(kind, inode) => proc.namespaces.get(*kind) == Some(*inode),
+            FilterTerm::NsPid(pid) => proc.inner_pid() == Some(*pid),
+        }
+    }
+
+    /// whether this term needs `FilterContext::sockets` to be populated
+    pub fn needs_sockets(&self) -> bool {
+        matches!(self, FilterTerm::Port(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cgroup::parse_cgroup, namespace::Namespaces};
+
+    #[test]
+    fn test_parse_filter_terms() {
+        let terms = parse_filter("node port:3000");
+
+        assert_eq!(
+            terms,
+            vec![
+                FilterTerm::NameOrPid(String::from("node")),
+                FilterTerm::Port(3000)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_ignores_unfinished_keyed_terms() {
+        assert!(parse_filter("port:").is_empty());
+        assert!(parse_filter("port:30a").is_empty());
+        assert!(parse_filter("state:zom").is_empty());
+    }
+
+    #[test]
+    fn test_state_term_matches() {
+        let ctx = FilterContext { sockets: &[] };
+        let zombie = Proc {
+            state: RunState::Zombie,
+            ..Default::default()
+        };
+        let sleeping = Proc {
+            state: RunState::Sleeping,
+            ..Default::default()
+        };
+
+        let terms = parse_filter("state:Z");
+
+        assert!(terms[0].matches(&zombie, &ctx));
+        assert!(!terms[0].matches(&sleeping, &ctx));
+    }
+
+    #[test]
+    fn test_user_term_matches_exactly() {
+        let ctx = FilterContext { sockets: &[] };
+        let proc = Proc {
+            user: Some(String::from("alice")),
+            ..Default::default()
+        };
+
+        assert!(parse_filter("user:alice")[0].matches(&proc, &ctx));
+        assert!(!parse_filter("user:ali")[0].matches(&proc, &ctx));
+        assert!(parse_filter("user:").is_empty());
+        assert!(!parse_filter("user:n/a")[0].matches(&proc, &ctx));
+        assert!(parse_filter("user:n/a")[0].matches(&Proc::default(), &ctx));
+    }
+
+    #[test]
+    fn test_unit_and_container_terms() {
+        let ctx = FilterContext { sockets: &[] };
+        let nginx = Proc {
+            cgroup: parse_cgroup("0::/system.slice/nginx.service\n"),
+            ..Default::default()
+        };
+        let container = Proc {
+            cgroup: parse_cgroup(
+                "0::/system.slice/docker-3f4c2a1b9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a.scope\n",
+            ),
+            ..Default::default()
+        };
+
+        let unit = &parse_filter("unit:nginx")[0];
+        assert!(unit.matches(&nginx, &ctx));
+        assert!(!unit.matches(&container, &ctx));
+
+        let id = &parse_filter("container:3f4c2a")[0];
+        assert!(id.matches(&container, &ctx));
+        assert!(!id.matches(&nginx, &ctx));
+    }
+
+    #[test]
+    fn test_cgroup_term_matches_group_and_children() {
+        let ctx = FilterContext { sockets: &[] };
+        let nginx = Proc {
+            cgroup: parse_cgroup("0::/system.slice/nginx.service\n"),
+            ..Default::default()
+        };
+
+        assert!(parse_filter("cgroup:/system.slice")[0].matches(&nginx, &ctx));
+        assert!(parse_filter("cgroup:/system.slice/nginx.service")[0].matches(&nginx, &ctx));
+        assert!(parse_filter("cgroup:/")[0].matches(&nginx, &ctx));
+        assert!(!parse_filter("cgroup:/system")[0].matches(&nginx, &ctx));
+        assert!(!parse_filter("cgroup:/user.slice")[0].matches(&nginx, &ctx));
+    }
+
+    #[test]
+    fn test_namespace_and_nspid_terms() {
+        let ctx = FilterContext { sockets: &[] };
+        let contained = Proc {
+            pid: 4242,
+            namespaces: Namespaces {
+                net: Some(4026532000),
+                ..Default::default()
+            },
+            ns_pids: vec![4242, 7],
+            ..Default::default()
+        };
+        let host = Proc {
+            pid: 7,
+            ns_pids: vec![7],
+            ..Default::default()
+        };
+
+        let net = &parse_filter("ns:net:4026532000")[0];
+        assert!(net.matches(&contained, &ctx));
+        assert!(!net.matches(&host, &ctx));
+
+        let nspid = &parse_filter("nspid:7")[0];
+        assert!(nspid.matches(&contained, &ctx));
+        assert!(!nspid.matches(&host, &ctx));
+
+        assert!(parse_filter("ns:net:").is_empty());
+        assert!(parse_filter("ns:cgroup:1").is_empty());
+    }
+
+    #[test]
+    fn test_parse_filter_unknown_key_is_plain_text() {
+        assert_eq!(
+            parse_filter("foo:bar"),
+            vec![FilterTerm::NameOrPid(String::from("foo:bar"))]
+        );
+    }
+}
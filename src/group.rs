@@ -0,0 +1,217 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use crate::{proc::Proc, proc_mem::MemoryMetric};
+
+/// what the process table collapses processes by
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GroupBy {
+    None,
+    Name,
+    Exe,
+    User,
+    Parent,
+    Cgroup,
+}
+
+impl Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            GroupBy::None => "none",
+            GroupBy::Name => "name",
+            GroupBy::Exe => "exe",
+            GroupBy::User => "user",
+            GroupBy::Parent => "parent",
+            GroupBy::Cgroup => "cgroup",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl GroupBy {
+    pub fn next(&self) -> GroupBy {
+        match self {
+            GroupBy::None => GroupBy::Name,
+            GroupBy::Name => GroupBy::Exe,
+            GroupBy::Exe => GroupBy::User,
+            GroupBy::User => GroupBy::Parent,
+            GroupBy::Parent => GroupBy::Cgroup,
+            GroupBy::Cgroup => GroupBy::None,
+        }
+    }
+}
+
+/// processes sharing the same name, exe, user, parent or cgroup owner with their usage added together
+pub struct ProcGroup {
+    pub key: String,
+    /// indices into the slice of processes the group was built from, in the same order
+    pub members: Vec<usize>,
+    pub memory: u64,
+    pub cpu_usage: f32,
+    pub disk_usage_read: u64,
+    pub disk_usage_written: u64,
+    /// `None` when no member has a known rate
+    pub net_rx: Option<u64>,
+    pub net_tx: Option<u64>,
+    pub min_pid: u32,
+}
+
+/// the key `proc` is grouped under, `None` when `by` is `GroupBy::None`
+pub fn group_key(proc: &Proc, by: GroupBy, names: &HashMap<u32, &str>) -> Option<String> {
+    let or_na = |s: &Option<String>| s.clone().unwrap_or(String::from("n/a"));
+
+    match by {
+        GroupBy::None => None,
+        GroupBy::Name => Some(or_na(&proc.name)),
+        GroupBy::Exe => Some(or_na(&proc.exe)),
+        GroupBy::User => Some(or_na(&proc.user)),
+        GroupBy::Parent => Some(match proc.ppid {
+            Some(ppid) => format!("{} {}", ppid, names.get(&ppid).unwrap_or(&"")),
+            None => String::from("n/a"),
+        }),
+        // processes without a recognizable owner are grouped by their raw path
+        GroupBy::Cgroup => Some(match &proc.cgroup {
+            Some(cgroup) => cgroup
+                .owner
+                .as_ref()
+                .map_or(cgroup.path.clone(), |o| o.to_string()),
+            None => String::from("n/a"),
+        }),
+    }
+}
+
+/// groups `procs` by `by`, memory is added up according to `metric`
+///
+/// groups come out in the order their first member appears in `procs`
+pub fn group_procs(procs: &[Proc], by: GroupBy, metric: MemoryMetric) -> Vec<ProcGroup> {
+    let names: HashMap<u32, &str> = procs
+        .iter()
+        .filter_map(|p| p.name.as_deref().map(|name| (p.pid, name)))
+        .collect();
+
+    let mut ret: Vec<ProcGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (i, proc) in procs.iter().enumerate() {
+        let key = match group_key(proc, by, &names) {
+            Some(key) => key,
+            None => continue,
+        };
+
+        let idx = *index.entry(key.clone()).or_insert_with(|| {
+            ret.push(ProcGroup {
+                key,
+                members: Vec::new(),
+                memory: 0,
+                cpu_usage: 0.0,
+                disk_usage_read: 0,
+                disk_usage_written: 0,
+                net_rx: None,
+                net_tx: None,
+                min_pid: u32::MAX,
+            });
+            ret.len() - 1
+        });
+
+        let group = &mut ret[idx];
+        group.members.push(i);
+        group.memory += proc.memory_by(metric).unwrap_or(0);
+        group.cpu_usage += proc.cpu_usage;
+        group.disk_usage_read += proc.disk_usage_read;
+        group.disk_usage_written += proc.disk_usage_written;
+        group.net_rx = add_rates(group.net_rx, proc.net_rx);
+        group.net_tx = add_rates(group.net_tx, proc.net_tx);
+        group.min_pid = group.min_pid.min(proc.pid);
+    }
+
+    ret
+}
+
+fn add_rates(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (None, None) => None,
+        _ => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgroup::parse_cgroup;
+
+    fn proc(pid: u32, ppid: u32, name: &str, user: &str, memory: u64, cpu_usage: f32) -> Proc {
+        Proc {
+            pid,
+            ppid: Some(ppid),
+            name: Some(String::from(name)),
+            user: Some(String::from(user)),
+            memory,
+            cpu_usage,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_by_name_sums_usage() {
+        let procs = vec![
+            proc(1, 0, "chrome", "ci", 100, 1.0),
+            proc(2, 1, "bash", "ci", 10, 0.5),
+            proc(3, 1, "chrome", "ci", 200, 2.0),
+        ];
+
+        let groups = group_procs(&procs, GroupBy::Name, MemoryMetric::Rss);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, "chrome");
+        assert_eq!(groups[0].members, vec![0, 2]);
+        assert_eq!(groups[0].memory, 300);
+        assert_eq!(groups[0].cpu_usage, 3.0);
+        assert_eq!(groups[0].net_rx, None);
+    }
+
+    #[test]
+    fn test_group_by_parent_uses_parent_name() {
+        let procs = vec![
+            proc(1, 0, "init", "root", 0, 0.0),
+            proc(2, 1, "a", "root", 0, 0.0),
+            proc(3, 1, "b", "root", 0, 0.0),
+        ];
+
+        let groups = group_procs(&procs, GroupBy::Parent, MemoryMetric::Rss);
+
+        let init_children = groups
+            .iter()
+            .find(|g| g.key == "1 init")
+            .expect("expected a group for children of init");
+        assert_eq!(init_children.members, vec![1, 2]);
+        assert_eq!(init_children.min_pid, 2);
+    }
+
+    #[test]
+    fn test_group_by_cgroup_uses_owner() {
+        let mut procs = vec![
+            proc(1, 0, "nginx", "www", 0, 0.0),
+            proc(2, 1, "nginx", "www", 0, 0.0),
+            proc(3, 0, "bash", "root", 0, 0.0),
+        ];
+        procs[0].cgroup = parse_cgroup("0::/system.slice/nginx.service\n");
+        procs[1].cgroup = parse_cgroup("0::/system.slice/nginx.service\n");
+        procs[2].cgroup = parse_cgroup("0::/init.scope/odd\n");
+
+        let groups = group_procs(&procs, GroupBy::Cgroup, MemoryMetric::Rss);
+
+        let keys: Vec<&str> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["nginx.service", "init.scope"]);
+        assert_eq!(groups[0].members, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_group_by_none_is_empty() {
+        let procs = vec![proc(1, 0, "init", "root", 0, 0.0)];
+
+        assert!(group_procs(&procs, GroupBy::None, MemoryMetric::Rss).is_empty());
+    }
+}
@@ -1,5 +1,8 @@
+use std::{fs, path::Path};
+
 use sysinfo::System;
 
+#[derive(Clone, Default)]
 pub struct Info {
     pub long_os_version: Option<String>,
     pub kernel_long_version: String,
@@ -26,9 +29,76 @@ pub fn read_info() -> Info {
     }
 }
 
+/// the parts of system info that change while chadtop is running
+#[derive(Clone, Default)]
+pub struct Status {
+    pub load_one: f64,
+    pub load_five: f64,
+    pub load_fifteen: f64,
+    /// seconds since boot
+    pub uptime: u64,
+    /// seconds since the unix epoch the system booted at
+    pub boot_time: u64,
+    pub process_count: usize,
+    /// every thread on the system, `None` when /proc/loadavg can't be read
+    pub thread_count: Option<usize>,
+}
+
+/// reads the load average, uptime and process and thread counts
+///
+/// the System argument should already be refreshed, it's only used to count processes
+pub fn read_status(sys: &System) -> Status {
+    read_status_from(sys, Path::new("/proc"))
+}
+
+pub fn read_status_from(sys: &System, proc_root: &Path) -> Status {
+    let load = System::load_average();
+
+    Status {
+        load_one: load.one,
+        load_five: load.five,
+        load_fifteen: load.fifteen,
+        uptime: System::uptime(),
+        boot_time: System::boot_time(),
+        process_count: sys.processes().len(),
+        thread_count: fs::read_to_string(proc_root.join("loadavg"))
+            .ok()
+            .and_then(|content| parse_loadavg_threads(&content)),
+    }
+}
+
+/// the 4th field of /proc/loadavg is `running/total` scheduling entities, i.e. threads
+///
+/// # Examples
+/// ```rust
+/// parse_loadavg_threads("0.52 0.58 0.59 2/1024 4242\n"); // Some(1024)
+/// ```
+fn parse_loadavg_threads(content: &str) -> Option<usize> {
+    content
+        .split_whitespace()
+        .nth(3)
+        .and_then(|field| field.split_once('/'))
+        .and_then(|(_, total)| total.parse().ok())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_read_status_threads_from_loadavg() {
+        let root = temp_path("loadavg");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("loadavg"), "0.52 0.58 0.59 2/1024 4242\n").unwrap();
+
+        let status = read_status_from(&System::new(), &root);
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(status.thread_count, Some(1024));
+        assert!(status.boot_time > 0);
+        assert_eq!(parse_loadavg_threads("garbage"), None);
+    }
 
     #[test]
     fn test_read_info_sanity() {
@@ -1,46 +1,123 @@
+pub mod alerts;
+pub mod audit;
+pub mod cgroup;
+pub mod cgroup_tree;
+pub mod clock;
 pub mod cpu;
+pub mod cpu_info;
+pub mod cpu_time;
+pub mod crashloop;
+pub mod events;
+pub mod filter;
+pub mod group;
 pub mod info;
+pub mod maps;
+pub mod namespace;
+pub mod net;
+pub mod pause;
+pub mod power;
+pub mod problems;
 pub mod proc;
+pub mod proc_mem;
+pub mod psi;
 pub mod ram;
+pub mod record;
+pub mod sensors;
+pub mod sessions;
+pub mod source;
 pub mod state;
+#[cfg(test)]
+mod test_util;
+pub mod throttle;
 pub mod ui;
+pub mod watchdog;
 
 use std::env;
 use std::io::{self, Result};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 use std::time::{Duration, Instant};
 
+use alerts::{default_alerts_path, AlertEngine};
+use audit::{default_audit_path, AuditLog};
 use ratatui::{
     crossterm::event::{self, Event},
     prelude::Backend,
     Terminal,
 };
+use record::{Recorder, Recording, Replay};
 use state::State;
 use ui::ui;
 
 fn main() -> Result<()> {
-    let debug_mode: bool;
     let args: Vec<String> = env::args().collect();
-    if args.contains(&String::from("-d")) {
-        debug_mode = true;
-    } else {
-        debug_mode = false;
-    }
+    let debug_mode = args.contains(&String::from("-d"));
+
+    // opened before the terminal is taken over so a bad path is a readable error
+    let recorder = arg_value(&args, "--record")
+        .map(|path| Recorder::create(Path::new(path)))
+        .transpose()?;
+    let replay = arg_value(&args, "--replay")
+        .map(|path| Recording::open(Path::new(path)))
+        .transpose()?
+        .map(Replay::new);
 
     let mut terminal = ratatui::init();
 
+    // quit the normal way on SIGTERM and SIGHUP so cpu limits and the terminal get cleaned up,
+    // panics unwind through `state` which does the same
+    let terminate = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGHUP] {
+        signal_hook::flag::register(signal, Arc::clone(&terminate))?;
+    }
+
     let mut state = State::new(debug_mode);
-    let _ = run(&mut terminal, &mut state);
+    let alerts_path = arg_value(&args, "--alerts")
+        .map(PathBuf::from)
+        .or_else(default_alerts_path);
+    if let Some(path) = alerts_path {
+        state.alerts = AlertEngine::load(&path);
+    }
+    let audit_path = arg_value(&args, "--audit-log")
+        .map(PathBuf::from)
+        .or_else(default_audit_path);
+    if let Some(path) = audit_path {
+        state.audit_log = AuditLog::open(path);
+    }
+    // rule actions stay dry runs unless asked for
+    state.alerts.watchdog.armed = args.contains(&String::from("--watchdog"));
+    state.recorder = recorder;
+    if replay.is_some() {
+        state.replay = replay;
+        state.refresh();
+    }
+    let _ = run(&mut terminal, &mut state, &terminate);
 
     ratatui::restore();
 
     Ok(())
 }
 
-fn run<B: Backend>(terminal: &mut Terminal<B>, state: &mut State) -> io::Result<()> {
+/// the argument after `flag`, i.e. the file of `--record FILE`
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|idx| args.get(idx + 1))
+        .map(String::as_str)
+}
+
+fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    state: &mut State,
+    terminate: &AtomicBool,
+) -> io::Result<()> {
     let mut elapsed = Instant::now();
     let refresh_interval = Duration::new(1, 0);
 
-    while !state.exit {
+    while !state.exit && !terminate.load(Ordering::Relaxed) {
         terminal.draw(|f| ui(f, state))?;
 
         if event::poll(Duration::new(1, 0))? {
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::proc_mem::kb_field;
+
+#[derive(Clone)]
+pub struct Mapping {
+    pub start: u64,
+    pub end: u64,
+    pub perms: String,
+    /// backing file or pseudo path like `[heap]`, `None` for anonymous mappings
+    pub path: Option<String>,
+    /// only known when `smaps` is readable
+    pub rss: Option<u64>,
+}
+
+impl Mapping {
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+/// every mapping of a single file (or all anonymous mappings) added together
+pub struct MappingGroup {
+    pub name: String,
+    pub count: usize,
+    pub size: u64,
+    pub rss: u64,
+}
+
+/// reads the memory mappings of `pid` from `/proc/<pid>/smaps`, falling back to
+/// `/proc/<pid>/maps` (without rss) if smaps can't be read
+pub fn read_maps(pid: u32) -> Vec<Mapping> {
+    read_maps_from(&Path::new("/proc").join(pid.to_string()))
+}
+
+/// same as `read_maps` but reads from a `/proc/<pid>` style directory
+pub fn read_maps_from(pid_root: &Path) -> Vec<Mapping> {
+    if let Ok(content) = fs::read_to_string(pid_root.join("smaps")) {
+        return parse_smaps(&content);
+    }
+
+    fs::read_to_string(pid_root.join("maps"))
+        .map(|content| content.lines().filter_map(parse_map_line).collect())
+        .unwrap_or_default()
+}
+
+/// groups mappings by their backing file, sorted by rss (then size) descending
+pub fn group_by_file(maps: &[Mapping]) -> Vec<MappingGroup> {
+    let mut groups: HashMap<&str, MappingGroup> = HashMap::new();
+
+    for map in maps {
+        let name = map.path.as_deref().unwrap_or("[anon]");
+        let group = groups.entry(name).or_insert(MappingGroup {
+            name: name.to_string(),
+            count: 0,
+            size: 0,
+            rss: 0,
+        });
+        group.count += 1;
+        group.size += map.size();
+        group.rss += map.rss.unwrap_or(0);
+    }
+
+    let mut ret: Vec<MappingGroup> = groups.into_values().collect();
+    ret.sort_by(|a, b| b.rss.cmp(&a.rss).then(b.size.cmp(&a.size)));
+    ret
+}
+
+/// parses a `maps` style header line i.e.
+/// `7f2c4a000000-7f2c4a021000 r-xp 00000000 08:01 1234  /usr/lib/libc.so.6`
+fn parse_map_line(line: &str) -> Option<Mapping> {
+    let mut fields = line.split_whitespace();
+
+    let (start, end) = fields.next()?.split_once('-')?;
+    let start = u64::from_str_radix(start, 16).ok()?;
+    let end = u64::from_str_radix(end, 16).ok()?;
+    let perms = fields.next()?.to_string();
+
+    // offset, dev and inode
+    let mut fields = fields.skip(3);
+    let path = fields.next().map(|first| {
+        // paths can contain spaces, put them back together
+        fields.fold(first.to_string(), |acc, f| acc + " " + f)
+    });
+
+    Some(Mapping {
+        start,
+        end,
+        perms,
+        path,
+        rss: None,
+    })
+}
+
+fn parse_smaps(content: &str) -> Vec<Mapping> {
+    let mut ret: Vec<Mapping> = Vec::new();
+
+    for line in content.lines() {
+        if let Some(map) = parse_map_line(line) {
+            ret.push(map);
+        } else if let Some(last) = ret.last_mut() {
+            if let Some(rss) = kb_field(line, "Rss:") {
+                last.rss = Some(rss);
+            }
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMAPS: &str =
+        "55d4c0a00000-55d4c0a21000 r-xp 00000000 08:01 1234                       /usr/bin/worker
+Size:                132 kB
+Rss:                 100 kB
+VmFlags: rd ex mr mw me dw
+7f2c4a000000-7f2c4a200000 rw-p 00000000 00:00 0
+Size:               2048 kB
+Rss:                1024 kB
+VmFlags: rd wr mr mw me ac
+7f2c4b000000-7f2c4b021000 r-xp 00000000 08:01 5678                       /usr/lib/libc.so.6
+Size:                132 kB
+Rss:                  64 kB
+7f2c4b021000-7f2c4b022000 r--p 00021000 08:01 5678                       /usr/lib/libc.so.6
+Size:                  4 kB
+Rss:                   4 kB
+";
+
+    #[test]
+    fn test_parse_smaps() {
+        let maps = parse_smaps(SMAPS);
+
+        assert_eq!(maps.len(), 4);
+        assert_eq!(maps[0].start, 0x55d4c0a00000);
+        assert_eq!(maps[0].perms, "r-xp");
+        assert_eq!(maps[0].path.as_deref(), Some("/usr/bin/worker"));
+        assert_eq!(maps[0].rss, Some(100 * 1024));
+        assert_eq!(maps[1].path, None);
+        assert_eq!(maps[1].size(), 0x200000);
+    }
+
+    #[test]
+    fn test_parse_map_line_with_spaces_in_path() {
+        let map =
+            parse_map_line("00400000-00401000 r--p 00000000 08:01 42   /tmp/my file (deleted)")
+                .expect("expected a mapping");
+
+        assert_eq!(map.path.as_deref(), Some("/tmp/my file (deleted)"));
+        assert_eq!(map.rss, None);
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        let groups = group_by_file(&parse_smaps(SMAPS));
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].name, "[anon]");
+        assert_eq!(groups[0].rss, 1024 * 1024);
+
+        let libc = groups
+            .iter()
+            .find(|g| g.name == "/usr/lib/libc.so.6")
+            .expect("expected libc group");
+        assert_eq!(libc.count, 2);
+        assert_eq!(libc.rss, 68 * 1024);
+        assert_eq!(libc.size, 0x22000);
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NsKind {
+    Pid,
+    Net,
+    Mnt,
+    User,
+    Uts,
+    Ipc,
+}
+
+impl Display for NsKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            NsKind::Pid => "pid",
+            NsKind::Net => "net",
+            NsKind::Mnt => "mnt",
+            NsKind::User => "user",
+            NsKind::Uts => "uts",
+            NsKind::Ipc => "ipc",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl NsKind {
+    pub const ALL: [NsKind; 6] = [
+        NsKind::Pid,
+        NsKind::Net,
+        NsKind::Mnt,
+        NsKind::User,
+        NsKind::Uts,
+        NsKind::Ipc,
+    ];
+
+    /// parses the name used in /proc/<pid>/ns, i.e. `net`
+    pub fn parse(s: &str) -> Option<NsKind> {
+        NsKind::ALL.into_iter().find(|kind| kind.to_string() == s)
+    }
+
+    /// the kind after this one in `ALL`, `None` after the last one
+    pub fn next(&self) -> Option<NsKind> {
+        let idx = NsKind::ALL.iter().position(|kind| kind == self)?;
+        NsKind::ALL.get(idx + 1).copied()
+    }
+}
+
+/// the inode of every namespace a process is in, two processes share a namespace when the inodes
+/// match. A namespace is `None` when its link can't be read, usually other users' processes
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Namespaces {
+    pub pid: Option<u64>,
+    pub net: Option<u64>,
+    pub mnt: Option<u64>,
+    pub user: Option<u64>,
+    pub uts: Option<u64>,
+    pub ipc: Option<u64>,
+}
+
+impl Namespaces {
+    pub fn get(&self, kind: NsKind) -> Option<u64> {
+        match kind {
+            NsKind::Pid => self.pid,
+            NsKind::Net => self.net,
+            NsKind::Mnt => self.mnt,
+            NsKind::User => self.user,
+            NsKind::Uts => self.uts,
+            NsKind::Ipc => self.ipc,
+        }
+    }
+}
+
+/// reads the /proc/<pid>/ns/* links
+pub fn read_namespaces(pid: u32) -> Namespaces {
+    read_namespaces_from(&Path::new("/proc").join(pid.to_string()))
+}
+
+pub fn read_namespaces_from(pid_root: &Path) -> Namespaces {
+    let read = |kind: NsKind| {
+        fs::read_link(pid_root.join("ns").join(kind.to_string()))
+            .ok()
+            .and_then(|link| link.to_str().and_then(parse_ns_link))
+    };
+
+    Namespaces {
+        pid: read(NsKind::Pid),
+        net: read(NsKind::Net),
+        mnt: read(NsKind::Mnt),
+        user: read(NsKind::User),
+        uts: read(NsKind::Uts),
+        ipc: read(NsKind::Ipc),
+    }
+}
+
+/// parses the target of a namespace link, i.e. `net:[4026531840]`
+fn parse_ns_link(link: &str) -> Option<u64> {
+    let (_, inode) = link.split_once(":[")?;
+    inode.strip_suffix(']')?.parse().ok()
+}
+
+/// reads the `NSpid` line of /proc/<pid>/status
+pub fn read_ns_pids(pid: u32) -> Vec<u32> {
+    read_ns_pids_from(&Path::new("/proc").join(pid.to_string()))
+}
+
+pub fn read_ns_pids_from(pid_root: &Path) -> Vec<u32> {
+    fs::read_to_string(pid_root.join("status"))
+        .map(|content| parse_ns_pids(&content))
+        .unwrap_or_default()
+}
+
+/// the pid of the process in every pid namespace it's visible in, from the one /proc is mounted
+/// in down to its own. Only one pid when the process isn't in a nested pid namespace
+fn parse_ns_pids(status: &str) -> Vec<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("NSpid:"))
+        .map(|pids| {
+            pids.split_whitespace()
+                .filter_map(|pid| pid.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_parse_ns_link() {
+        assert_eq!(parse_ns_link("net:[4026531840]"), Some(4026531840));
+        assert_eq!(
+            parse_ns_link("pid_for_children:[4026531836]"),
+            Some(4026531836)
+        );
+        assert_eq!(parse_ns_link("net:4026531840"), None);
+    }
+
+    #[test]
+    fn test_parse_ns_pids() {
+        let status = "Name:\tnginx\nTgid:\t4242\nNSpid:\t4242\t7\nPPid:\t1\n";
+
+        assert_eq!(parse_ns_pids(status), vec![4242, 7]);
+        assert_eq!(parse_ns_pids("Name:\tinit\nNSpid:\t1\n"), vec![1]);
+        assert!(parse_ns_pids("Name:\told kernel\n").is_empty());
+    }
+
+    #[test]
+    fn test_read_namespaces_from_fixture() {
+        let pid_root = temp_path("ns");
+        fs::create_dir_all(pid_root.join("ns")).unwrap();
+        symlink("net:[4026532000]", pid_root.join("ns/net")).unwrap();
+        symlink("pid:[4026531836]", pid_root.join("ns/pid")).unwrap();
+        fs::write(pid_root.join("status"), "NSpid:\t100\t1\n").unwrap();
+
+        let namespaces = read_namespaces_from(&pid_root);
+        let ns_pids = read_ns_pids_from(&pid_root);
+
+        fs::remove_dir_all(&pid_root).unwrap();
+        assert_eq!(namespaces.net, Some(4026532000));
+        assert_eq!(namespaces.get(NsKind::Pid), Some(4026531836));
+        assert_eq!(namespaces.mnt, None);
+        assert_eq!(ns_pids, vec![100, 1]);
+    }
+
+    #[test]
+    fn test_kind_parse_and_next() {
+        assert_eq!(NsKind::parse("uts"), Some(NsKind::Uts));
+        assert_eq!(NsKind::parse("cgroup"), None);
+        assert_eq!(NsKind::Pid.next(), Some(NsKind::Net));
+        assert_eq!(NsKind::Ipc.next(), None);
+    }
+}
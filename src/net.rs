@@ -0,0 +1,527 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    fs,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::Path,
+    time::Instant,
+};
+
+use crate::proc::Proc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Unix => "unix",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Clone)]
+pub struct Socket {
+    pub protocol: Protocol,
+    pub local: String,
+    pub local_port: Option<u16>,
+    pub remote: Option<String>,
+    pub state: String,
+    pub inode: u64,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+}
+
+/// read every tcp, udp and unix socket on the system and resolve which process owns it
+///
+/// only works on linux, on other platforms `/proc` doesn't exist so this is always empty
+pub fn read_sockets() -> Vec<Socket> {
+    read_sockets_from(Path::new("/proc"))
+}
+
+/// same as `read_sockets` but reads from `proc_root` instead of `/proc`
+///
+/// sockets owned by processes we aren't allowed to look into are still returned, they just don't
+/// have a pid
+pub fn read_sockets_from(proc_root: &Path) -> Vec<Socket> {
+    let net = proc_root.join("net");
+    let mut ret: Vec<Socket> = Vec::new();
+
+    for (file, protocol) in [
+        ("tcp", Protocol::Tcp),
+        ("tcp6", Protocol::Tcp6),
+        ("udp", Protocol::Udp),
+        ("udp6", Protocol::Udp6),
+    ] {
+        if let Ok(content) = fs::read_to_string(net.join(file)) {
+            ret.extend(parse_inet(&content, protocol));
+        }
+    }
+
+    if let Ok(content) = fs::read_to_string(net.join("unix")) {
+        ret.extend(parse_unix(&content));
+    }
+
+    let owners = read_socket_owners(proc_root);
+    for socket in ret.iter_mut() {
+        if let Some((pid, name)) = owners.get(&socket.inode) {
+            socket.pid = Some(*pid);
+            socket.process_name = name.clone();
+        }
+    }
+
+    ret
+}
+
+/// maps socket inodes to the pid and name of the process holding them by reading the
+/// `socket:[inode]` links in `/proc/<pid>/fd`
+fn read_socket_owners(proc_root: &Path) -> HashMap<u64, (u32, Option<String>)> {
+    let mut ret = HashMap::new();
+
+    let entries = match fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(_) => return ret,
+    };
+
+    for entry in entries.flatten() {
+        let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        let fds = match fs::read_dir(entry.path().join("fd")) {
+            Ok(fds) => fds,
+            Err(_) => continue,
+        };
+
+        let mut name: Option<String> = None;
+        for fd in fds.flatten() {
+            let inode = fs::read_link(fd.path()).ok().and_then(|link| {
+                link.to_str()
+                    .and_then(|s| s.strip_prefix("socket:["))
+                    .and_then(|s| s.strip_suffix(']'))
+                    .and_then(|s| s.parse::<u64>().ok())
+            });
+
+            if let Some(inode) = inode {
+                if name.is_none() {
+                    name = fs::read_to_string(entry.path().join("comm"))
+                        .ok()
+                        .map(|s| s.trim_end().to_string());
+                }
+                ret.entry(inode).or_insert((pid, name.clone()));
+            }
+        }
+    }
+
+    ret
+}
+
+/// tracks per-process network throughput
+///
+/// the kernel has no unprivileged per-process byte counters, but `/proc/<pid>/net/dev` shows the
+/// interfaces of the network namespace the process lives in. For processes in their own netns
+/// (containers, sandboxes, etc.) that is exactly their traffic. Processes sharing chadtop's netns
+/// would just show the host totals, so they get no rate instead. Every process in a netns shows
+/// the rate of the whole netns.
+pub struct NetThroughput {
+    own_netns: Option<u64>,
+    /// netns inode -> (rx bytes, tx bytes, when they were read)
+    samples: HashMap<u64, (u64, u64, Instant)>,
+    /// netns inode -> (rx bytes/s, tx bytes/s)
+    rates: HashMap<u64, (u64, u64)>,
+    pid_netns: HashMap<u32, u64>,
+}
+
+impl Default for NetThroughput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetThroughput {
+    pub fn new() -> NetThroughput {
+        NetThroughput {
+            own_netns: read_netns(Path::new("/proc/self")),
+            samples: HashMap::new(),
+            rates: HashMap::new(),
+            pid_netns: HashMap::new(),
+        }
+    }
+
+    /// reads the counters of every netns `pids` live in and updates the rates
+    pub fn sample(&mut self, pids: impl Iterator<Item = u32>) {
+        self.sample_from(Path::new("/proc"), pids, Instant::now());
+    }
+
+    /// same as `sample` but reads from `proc_root` instead of `/proc`
+    pub fn sample_from(&mut self, proc_root: &Path, pids: impl Iterator<Item = u32>, now: Instant) {
+        self.pid_netns.clear();
+        let mut seen: HashMap<u64, (u64, u64)> = HashMap::new();
+
+        for pid in pids {
+            let pid_root = proc_root.join(pid.to_string());
+            let netns = match read_netns(&pid_root) {
+                Some(netns) if Some(netns) != self.own_netns => netns,
+                _ => continue,
+            };
+            self.pid_netns.insert(pid, netns);
+
+            if seen.contains_key(&netns) {
+                continue;
+            }
+
+            if let Some(counters) = fs::read_to_string(pid_root.join("net/dev"))
+                .ok()
+                .and_then(|content| parse_net_dev(&content))
+            {
+                seen.insert(netns, counters);
+            }
+        }
+
+        self.rates.clear();
+        for (netns, (rx, tx)) in seen.iter() {
+            if let Some((prev_rx, prev_tx, prev_time)) = self.samples.get(netns) {
+                let secs = now.duration_since(*prev_time).as_secs_f64();
+                if secs > 0.0 {
+                    self.rates.insert(
+                        *netns,
+                        (
+                            (rx.saturating_sub(*prev_rx) as f64 / secs) as u64,
+                            (tx.saturating_sub(*prev_tx) as f64 / secs) as u64,
+                        ),
+                    );
+                }
+            }
+        }
+
+        self.samples = seen
+            .into_iter()
+            .map(|(netns, (rx, tx))| (netns, (rx, tx, now)))
+            .collect();
+    }
+
+    /// fills in `net_rx` and `net_tx` from the last sample
+    pub fn apply(&self, procs: &mut [Proc]) {
+        for proc in procs.iter_mut() {
+            let rate = self
+                .pid_netns
+                .get(&proc.pid)
+                .and_then(|netns| self.rates.get(netns));
+            proc.net_rx = rate.map(|r| r.0);
+            proc.net_tx = rate.map(|r| r.1);
+        }
+    }
+}
+
+/// reads the inode of the `ns/net` link of a `/proc/<pid>` directory
+fn read_netns(pid_root: &Path) -> Option<u64> {
+    fs::read_link(pid_root.join("ns/net"))
+        .ok()
+        .and_then(|link| {
+            link.to_str()
+                .and_then(|s| s.strip_prefix("net:["))
+                .and_then(|s| s.strip_suffix(']'))
+                .and_then(|s| s.parse::<u64>().ok())
+        })
+}
+
+/// sums received and transmitted bytes over every interface in `/proc/<pid>/net/dev` except
+/// loopback
+fn parse_net_dev(content: &str) -> Option<(u64, u64)> {
+    let mut rx: u64 = 0;
+    let mut tx: u64 = 0;
+    let mut found = false;
+
+    // first two lines are the column headers
+    for line in content.lines().skip(2) {
+        let (iface, counters) = match line.split_once(':') {
+            Some(x) => x,
+            None => continue,
+        };
+
+        if iface.trim() == "lo" {
+            continue;
+        }
+
+        let fields: Vec<u64> = counters
+            .split_whitespace()
+            .filter_map(|f| f.parse().ok())
+            .collect();
+        if fields.len() < 9 {
+            continue;
+        }
+
+        rx += fields[0];
+        tx += fields[8];
+        found = true;
+    }
+
+    found.then_some((rx, tx))
+}
+
+/// parses `/proc/net/{tcp,tcp6,udp,udp6}`
+fn parse_inet(content: &str, protocol: Protocol) -> Vec<Socket> {
+    let mut ret: Vec<Socket> = Vec::new();
+
+    // first line is the column header
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            continue;
+        }
+
+        let local = match parse_inet_addr(fields[1]) {
+            Some(addr) => addr,
+            None => continue,
+        };
+        let remote = parse_inet_addr(fields[2]);
+        let state = u8::from_str_radix(fields[3], 16).unwrap_or(0);
+        let inode = fields[9].parse::<u64>().unwrap_or(0);
+
+        ret.push(Socket {
+            protocol,
+            local: local.to_string(),
+            local_port: Some(local.port()),
+            remote: remote
+                .filter(|r| !r.ip().is_unspecified() || r.port() != 0)
+                .map(|r| r.to_string()),
+            state: inet_state_str(protocol, state).to_string(),
+            inode,
+            pid: None,
+            process_name: None,
+        });
+    }
+
+    ret
+}
+
+/// parses the `ADDR:PORT` hex pairs the kernel writes, the address is written as 32 bit words
+/// in host byte order
+fn parse_inet_addr(s: &str) -> Option<SocketAddr> {
+    let (addr, port) = s.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    match addr.len() {
+        8 => {
+            let word = u32::from_str_radix(addr, 16).ok()?;
+            Some(SocketAddr::from((Ipv4Addr::from(word.to_ne_bytes()), port)))
+        }
+        32 => {
+            let mut bytes = [0u8; 16];
+            for i in 0..4 {
+                let word = u32::from_str_radix(&addr[i * 8..i * 8 + 8], 16).ok()?;
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+            }
+            Some(SocketAddr::from((Ipv6Addr::from(bytes), port)))
+        }
+        _ => None,
+    }
+}
+
+fn inet_state_str(protocol: Protocol, state: u8) -> &'static str {
+    match (protocol, state) {
+        (Protocol::Udp | Protocol::Udp6, 0x01) => "estab",
+        (Protocol::Udp | Protocol::Udp6, 0x07) => "unconn",
+        (_, 0x01) => "established",
+        (_, 0x02) => "syn-sent",
+        (_, 0x03) => "syn-recv",
+        (_, 0x04) => "fin-wait1",
+        (_, 0x05) => "fin-wait2",
+        (_, 0x06) => "time-wait",
+        (_, 0x07) => "close",
+        (_, 0x08) => "close-wait",
+        (_, 0x09) => "last-ack",
+        (_, 0x0A) => "listen",
+        (_, 0x0B) => "closing",
+        _ => "unknown",
+    }
+}
+
+/// parses `/proc/net/unix`
+fn parse_unix(content: &str) -> Vec<Socket> {
+    // the kernel sets __SO_ACCEPTCON in the flags column for listening sockets
+    const ACCEPTCON: u32 = 0x10000;
+
+    let mut ret: Vec<Socket> = Vec::new();
+
+    // first line is the column header
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 7 {
+            continue;
+        }
+
+        let flags = u32::from_str_radix(fields[3], 16).unwrap_or(0);
+        let state = u8::from_str_radix(fields[5], 16).unwrap_or(0);
+        let inode = fields[6].parse::<u64>().unwrap_or(0);
+
+        let state = if flags & ACCEPTCON != 0 {
+            "listen"
+        } else {
+            match state {
+                0x01 => "unconn",
+                0x02 => "connecting",
+                0x03 => "connected",
+                0x04 => "disconnecting",
+                _ => "unknown",
+            }
+        };
+
+        ret.push(Socket {
+            protocol: Protocol::Unix,
+            local: fields.get(7).unwrap_or(&"*").to_string(),
+            local_port: None,
+            remote: None,
+            state: state.to_string(),
+            inode,
+            pid: None,
+            process_name: None,
+        });
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_parse_inet_tcp() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 12345 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:D431 01 00000000:00000000 00:00000000 00000000  1000        0 12346 1 0000000000000000 20 4 30 10 -1
+";
+
+        let sockets = parse_inet(content, Protocol::Tcp);
+
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].local, "127.0.0.1:8080");
+        assert_eq!(sockets[0].local_port, Some(8080));
+        assert_eq!(sockets[0].remote, None);
+        assert_eq!(sockets[0].state, "listen");
+        assert_eq!(sockets[0].inode, 12345);
+        assert_eq!(sockets[1].remote.as_deref(), Some("127.0.0.1:54321"));
+        assert_eq!(sockets[1].state, "established");
+    }
+
+    #[test]
+    fn test_parse_inet_tcp6() {
+        let content = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0BB8 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 777 1 0000000000000000 100 0 0 10 0
+";
+
+        let sockets = parse_inet(content, Protocol::Tcp6);
+
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local, "[::1]:3000");
+        assert_eq!(sockets[0].local_port, Some(3000));
+    }
+
+    #[test]
+    fn test_parse_unix() {
+        let content = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 23597 /run/systemd/notify
+0000000000000000: 00000003 00000000 00000000 0001 03 23600
+";
+
+        let sockets = parse_unix(content);
+
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].state, "listen");
+        assert_eq!(sockets[0].local, "/run/systemd/notify");
+        assert_eq!(sockets[1].state, "connected");
+        assert_eq!(sockets[1].local, "*");
+        assert_eq!(sockets[1].inode, 23600);
+    }
+
+    #[test]
+    fn test_parse_net_dev_skips_loopback() {
+        let content = "Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    5000      50    0    0    0     0          0         0     5000      50    0    0    0     0       0          0
+  eth0:    1200      10    0    0    0     0          0         0      300       3    0    0    0     0       0          0
+";
+
+        assert_eq!(parse_net_dev(content), Some((1200, 300)));
+    }
+
+    #[test]
+    fn test_net_throughput_rates() {
+        let root = temp_path("netdev");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("7/net")).unwrap();
+        fs::create_dir_all(root.join("7/ns")).unwrap();
+        std::os::unix::fs::symlink("net:[4026532000]", root.join("7/ns/net")).unwrap();
+
+        let write_dev = |rx: u64, tx: u64| {
+            fs::write(
+                root.join("7/net/dev"),
+                format!(
+                    "header\nheader\n  eth0: {} 0 0 0 0 0 0 0 {} 0 0 0 0 0 0 0\n",
+                    rx, tx
+                ),
+            )
+            .unwrap();
+        };
+
+        let mut throughput = NetThroughput {
+            own_netns: Some(1),
+            samples: HashMap::new(),
+            rates: HashMap::new(),
+            pid_netns: HashMap::new(),
+        };
+        let start = Instant::now();
+
+        write_dev(1000, 500);
+        throughput.sample_from(&root, [7].into_iter(), start);
+        write_dev(3000, 1500);
+        throughput.sample_from(
+            &root,
+            [7].into_iter(),
+            start + std::time::Duration::from_secs(2),
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(throughput.rates.get(&4026532000), Some(&(1000, 500)));
+    }
+
+    #[test]
+    fn test_read_sockets_from_resolves_owner() {
+        let root = temp_path("net");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("net")).unwrap();
+        fs::create_dir_all(root.join("42/fd")).unwrap();
+
+        fs::write(
+            root.join("net/tcp"),
+            "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 999 1 0000000000000000 100 0 0 10 0
+",
+        )
+        .unwrap();
+        fs::write(root.join("42/comm"), "node\n").unwrap();
+        std::os::unix::fs::symlink("socket:[999]", root.join("42/fd/3")).unwrap();
+
+        let sockets = read_sockets_from(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].pid, Some(42));
+        assert_eq!(sockets[0].process_name.as_deref(), Some("node"));
+    }
+}
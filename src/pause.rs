@@ -0,0 +1,253 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs, io,
+    path::Path,
+};
+
+use sysinfo::{Pid, Signal, System};
+
+use crate::{cgroup::in_cgroup, proc::Proc};
+
+/// how a process was paused by chadtop
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PauseKind {
+    /// sent SIGSTOP
+    Stopped,
+    /// in a cgroup frozen with cgroup.freeze
+    Frozen,
+}
+
+/// something `PauseTracker::resume_all` resumed
+#[derive(Clone, Debug, PartialEq)]
+pub enum Resumed {
+    Process(u32),
+    /// cgroup v2 path
+    Cgroup(String),
+}
+
+/// every process and cgroup chadtop paused, so they can be resumed before quitting
+#[derive(Default)]
+pub struct PauseTracker {
+    /// pid -> start time, so a process that reuses the pid isn't resumed by mistake
+    pids: HashMap<u32, u64>,
+    /// cgroup v2 paths
+    cgroups: BTreeSet<String>,
+}
+
+impl PauseTracker {
+    pub fn new() -> PauseTracker {
+        PauseTracker::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pids.is_empty() && self.cgroups.is_empty()
+    }
+
+    pub fn pid_count(&self) -> usize {
+        self.pids.len()
+    }
+
+    pub fn cgroup_count(&self) -> usize {
+        self.cgroups.len()
+    }
+
+    /// sends SIGSTOP to `pid`, returns whether the signal was sent. chadtop never stops itself
+    /// since nothing could resume it afterwards
+    pub fn pause(&mut self, sys: &System, pid: u32) -> bool {
+        if pid == std::process::id() {
+            return false;
+        }
+
+        let process = match sys.process(Pid::from_u32(pid)) {
+            Some(process) => process,
+            None => return false,
+        };
+
+        let sent = process.kill_with(Signal::Stop).unwrap_or(false);
+        if sent {
+            self.pids.insert(pid, process.start_time());
+        }
+        sent
+    }
+
+    /// sends SIGCONT to `pid`, also for processes chadtop didn't stop
+    pub fn resume(&mut self, sys: &System, pid: u32) -> bool {
+        self.pids.remove(&pid);
+
+        sys.process(Pid::from_u32(pid))
+            .and_then(|p| p.kill_with(Signal::Continue))
+            .unwrap_or(false)
+    }
+
+    /// freezes the cgroup at `path` under the cgroup2 mount `root`, refuses to freeze the cgroup
+    /// chadtop is in (or any above it) for the same reason as `pause`
+    pub fn freeze(&mut self, root: &Path, path: &str, own_path: Option<&str>) -> io::Result<()> {
+        if own_path.is_some_and(|own| in_cgroup(own, path)) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "refusing to freeze the cgroup chadtop runs in",
+            ));
+        }
+
+        set_frozen(root, path, true)?;
+        self.cgroups.insert(path.to_string());
+        Ok(())
+    }
+
+    pub fn thaw(&mut self, root: &Path, path: &str) -> io::Result<()> {
+        self.cgroups.remove(path);
+        set_frozen(root, path, false)
+    }
+
+    /// resumes everything chadtop paused, returns every process and cgroup along with whether it
+    /// was resumed
+    pub fn resume_all(&mut self, sys: &System, root: Option<&Path>) -> Vec<(Resumed, bool)> {
+        let mut ret = Vec::new();
+
+        let mut pids: Vec<u32> = self.pids.keys().copied().collect();
+        pids.sort();
+        for pid in pids {
+            let resumed = self.resume(sys, pid);
+            ret.push((Resumed::Process(pid), resumed));
+        }
+
+        for path in std::mem::take(&mut self.cgroups) {
+            let thawed = root.is_some_and(|root| set_frozen(root, &path, false).is_ok());
+            ret.push((Resumed::Cgroup(path), thawed));
+        }
+
+        ret
+    }
+
+    /// forgets processes that exited, `processes` has to be every process on the system
+    pub fn prune(&mut self, processes: &[Proc]) {
+        let alive: HashMap<u32, u64> = processes.iter().map(|p| (p.pid, p.start_time)).collect();
+        self.pids
+            .retain(|pid, start_time| alive.get(pid) == Some(start_time));
+    }
+
+    pub fn get(&self, proc: &Proc) -> Option<PauseKind> {
+        if self.pids.get(&proc.pid) == Some(&proc.start_time) {
+            return Some(PauseKind::Stopped);
+        }
+
+        let path = proc.cgroup.as_ref().and_then(|c| c.v2_path.as_deref())?;
+        self.cgroups
+            .iter()
+            .any(|group| in_cgroup(path, group))
+            .then_some(PauseKind::Frozen)
+    }
+}
+
+/// writes cgroup.freeze of the group at `path` under the cgroup2 mount `root`
+pub fn set_frozen(root: &Path, path: &str, frozen: bool) -> io::Result<()> {
+    let file = root
+        .join(path.trim_start_matches('/'))
+        .join("cgroup.freeze");
+    fs::write(file, if frozen { "1" } else { "0" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgroup::parse_cgroup;
+    use crate::test_util::temp_path;
+    use std::process::Command;
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate};
+
+    #[test]
+    fn test_pause_and_resume_child() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut sys = System::new();
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[Pid::from_u32(pid)]),
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        let state = || {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            let (_, rest) = stat.rsplit_once(") ").unwrap();
+            rest.chars().next().unwrap()
+        };
+
+        let mut tracker = PauseTracker::new();
+        assert!(tracker.pause(&sys, pid));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let paused = state();
+        assert_eq!(tracker.pid_count(), 1);
+
+        let results = tracker.resume_all(&sys, None);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let resumed = state();
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(paused, 'T');
+        assert_ne!(resumed, 'T');
+        assert_eq!(results, vec![(Resumed::Process(pid), true)]);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_never_pauses_itself() {
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        let mut tracker = PauseTracker::new();
+
+        assert!(!tracker.pause(&sys, std::process::id()));
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_freeze_and_thaw_fixture() {
+        let root = temp_path("freeze");
+        let group = root.join("system.slice/job.service");
+        fs::create_dir_all(&group).unwrap();
+        let mut tracker = PauseTracker::new();
+
+        let own = tracker.freeze(&root, "/system.slice", Some("/system.slice/chadtop.scope"));
+        tracker
+            .freeze(&root, "/system.slice/job.service", Some("/user.slice"))
+            .unwrap();
+        let frozen = fs::read_to_string(group.join("cgroup.freeze")).unwrap();
+
+        let job = Proc {
+            pid: 42,
+            cgroup: parse_cgroup("0::/system.slice/job.service\n"),
+            ..Default::default()
+        };
+        let kind = tracker.get(&job);
+
+        let results = tracker.resume_all(&System::new(), Some(&root));
+        let thawed = fs::read_to_string(group.join("cgroup.freeze")).unwrap();
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(own.is_err());
+        assert_eq!(frozen, "1");
+        assert_eq!(kind, Some(PauseKind::Frozen));
+        assert_eq!(thawed, "0");
+        assert_eq!(
+            results,
+            vec![(
+                Resumed::Cgroup(String::from("/system.slice/job.service")),
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn test_prune_forgets_reused_pids() {
+        let mut tracker = PauseTracker::new();
+        tracker.pids.insert(42, 100);
+        tracker.pids.insert(43, 100);
+
+        tracker.prune(&[Proc {
+            pid: 42,
+            start_time: 200,
+            ..Default::default()
+        }]);
+
+        assert!(tracker.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use std::{fs, path::Path};
+
+pub struct Battery {
+    pub name: String,
+    /// percent
+    pub capacity: Option<u8>,
+    /// Charging, Discharging, Full, Not charging or Unknown as the kernel reports it
+    pub status: Option<String>,
+    /// watts going in or out of the battery, always positive
+    pub power: Option<f64>,
+    /// seconds until empty, only while discharging
+    pub time_to_empty: Option<u64>,
+    /// seconds until full, only while charging
+    pub time_to_full: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct PowerStatus {
+    pub batteries: Vec<Battery>,
+    /// `None` when there is no mains or usb supply to ask, i.e. most desktops
+    pub ac_online: Option<bool>,
+}
+
+/// reads /sys/class/power_supply
+pub fn read_power() -> PowerStatus {
+    read_power_from(Path::new("/sys/class/power_supply"))
+}
+
+/// reads every supply under `root`, batteries of peripherals (mice, keyboards, etc.) are skipped
+pub fn read_power_from(root: &Path) -> PowerStatus {
+    let mut ret = PowerStatus::default();
+
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(_) => return ret,
+    };
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let dir = entry.path();
+        let read = |file: &str| {
+            fs::read_to_string(dir.join(file))
+                .ok()
+                .map(|s| s.trim().to_string())
+        };
+
+        match read("type").as_deref() {
+            Some("Mains") | Some("USB") => {
+                if let Some(online) = read("online") {
+                    ret.ac_online = Some(ret.ac_online.unwrap_or(false) || online == "1");
+                }
+            }
+            Some("Battery") if read("scope").as_deref() != Some("Device") => {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                ret.batteries.push(read_battery(name, &read));
+            }
+            _ => {}
+        }
+    }
+
+    ret.batteries.sort_by(|a, b| a.name.cmp(&b.name));
+    ret
+}
+
+/// builds a `Battery` out of its sysfs attributes
+///
+/// drivers report either energy (µWh) and power (µW) or charge (µAh) and current (µA), charge is
+/// converted to energy with the current voltage. Times come from the driver when it has them and
+/// are estimated from the current rate otherwise
+fn read_battery(name: String, read: &dyn Fn(&str) -> Option<String>) -> Battery {
+    let number = |file: &str| read(file).and_then(|s| s.parse::<f64>().ok());
+
+    let voltage = number("voltage_now").map(|uv| uv / 1e6);
+    let to_wh = |energy: &str, charge: &str| {
+        number(energy)
+            .map(|uwh| uwh / 1e6)
+            .or_else(|| Some(number(charge)? / 1e6 * voltage?))
+    };
+
+    let energy_now = to_wh("energy_now", "charge_now");
+    let energy_full = to_wh("energy_full", "charge_full");
+    let power = number("power_now")
+        .map(|uw| uw / 1e6)
+        .or_else(|| Some(number("current_now")? / 1e6 * voltage?))
+        .map(f64::abs);
+
+    let status = read("status");
+    let capacity = number("capacity")
+        .map(|c| c.clamp(0.0, 100.0) as u8)
+        .or_else(|| {
+            Some((energy_now? / energy_full?.max(f64::EPSILON) * 100.0).clamp(0.0, 100.0) as u8)
+        });
+
+    let rate = power.filter(|p| *p > 0.0);
+    let hours_to_secs = |hours: f64| (hours * 3600.0) as u64;
+
+    let (time_to_empty, time_to_full) = match status.as_deref() {
+        Some("Discharging") => (
+            number("time_to_empty_now")
+                .map(|s| s as u64)
+                .or_else(|| Some(hours_to_secs(energy_now? / rate?))),
+            None,
+        ),
+        Some("Charging") => (
+            None,
+            number("time_to_full_now")
+                .map(|s| s as u64)
+                .or_else(|| Some(hours_to_secs((energy_full? - energy_now?).max(0.0) / rate?))),
+        ),
+        _ => (None, None),
+    };
+
+    Battery {
+        name,
+        capacity,
+        status,
+        power,
+        time_to_empty,
+        time_to_full,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, write_file};
+
+    fn supply(root: &Path, name: &str, attributes: &[(&str, &str)]) {
+        for (file, content) in attributes {
+            write_file(&root.join(name).join(file), &format!("{}\n", content));
+        }
+    }
+
+    #[test]
+    fn test_discharging_energy_battery() {
+        let root = temp_path("power_energy");
+        supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("capacity", "50"),
+                ("energy_now", "25000000"),
+                ("energy_full", "50000000"),
+                ("power_now", "10000000"),
+            ],
+        );
+        // a wireless mouse shouldn't show up as a laptop battery
+        supply(
+            &root,
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+        );
+
+        let power = read_power_from(&root);
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(power.ac_online, Some(false));
+        assert_eq!(power.batteries.len(), 1);
+        let battery = &power.batteries[0];
+        assert_eq!(battery.name, "BAT0");
+        assert_eq!(battery.capacity, Some(50));
+        assert_eq!(battery.power, Some(10.0));
+        // 25 Wh at 10 W
+        assert_eq!(battery.time_to_empty, Some(9000));
+        assert_eq!(battery.time_to_full, None);
+    }
+
+    #[test]
+    fn test_charging_charge_battery() {
+        let root = temp_path("power_charge");
+        supply(&root, "ADP1", &[("type", "Mains"), ("online", "1")]);
+        supply(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("charge_now", "1000000"),
+                ("charge_full", "4000000"),
+                ("current_now", "2000000"),
+                ("voltage_now", "10000000"),
+            ],
+        );
+
+        let power = read_power_from(&root);
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(power.ac_online, Some(true));
+        let battery = &power.batteries[0];
+        // no capacity file so it comes from 10 Wh out of 40 Wh
+        assert_eq!(battery.capacity, Some(25));
+        assert_eq!(battery.power, Some(20.0));
+        // 30 Wh to go at 20 W
+        assert_eq!(battery.time_to_full, Some(5400));
+    }
+
+    #[test]
+    fn test_no_power_supplies() {
+        let power = read_power_from(Path::new("/nonexistent/power_supply"));
+
+        assert!(power.batteries.is_empty());
+        assert_eq!(power.ac_online, None);
+    }
+}
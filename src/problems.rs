@@ -0,0 +1,236 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crate::proc::{Proc, RunState};
+
+pub enum Problem {
+    /// exited but its parent hasn't called `wait` on it yet
+    Zombie {
+        pid: u32,
+        name: Option<String>,
+        ppid: Option<u32>,
+        parent_name: Option<String>,
+    },
+    /// its parent exited while chadtop was watching and it got adopted by init or a subreaper
+    Reparented {
+        pid: u32,
+        name: Option<String>,
+        original_ppid: u32,
+        ppid: Option<u32>,
+        parent_name: Option<String>,
+    },
+    /// has been in uninterruptible sleep for at least `since`
+    StuckDiskSleep {
+        pid: u32,
+        name: Option<String>,
+        since: Duration,
+    },
+}
+
+impl Problem {
+    pub fn pid(&self) -> u32 {
+        match self {
+            Problem::Zombie { pid, .. }
+            | Problem::Reparented { pid, .. }
+            | Problem::StuckDiskSleep { pid, .. } => *pid,
+        }
+    }
+
+    /// the process that can actually do something about the problem, a zombie can only go away
+    /// once its parent reaps it
+    pub fn actionable_pid(&self) -> u32 {
+        match self {
+            Problem::Zombie {
+                ppid: Some(ppid), ..
+            } => *ppid,
+            _ => self.pid(),
+        }
+    }
+}
+
+/// remembers what processes looked like on earlier refreshes, which is needed to tell that a
+/// process got reparented or how long it has been stuck in disk sleep
+///
+/// processes are keyed by (pid, start time) so a reused pid isn't mistaken for the old process
+#[derive(Default)]
+pub struct ProblemTracker {
+    first_ppid: HashMap<(u32, u64), Option<u32>>,
+    disk_sleep_since: HashMap<(u32, u64), Instant>,
+}
+
+impl ProblemTracker {
+    pub fn new() -> ProblemTracker {
+        ProblemTracker::default()
+    }
+
+    /// records `procs` and returns every problem found in them, `procs` should be every process
+    /// on the system and not just the filtered ones
+    ///
+    /// processes that were already orphaned when chadtop started can't be told apart from
+    /// daemons and aren't reported
+    pub fn update(
+        &mut self,
+        procs: &[Proc],
+        now: Instant,
+        disk_sleep_threshold: Duration,
+    ) -> Vec<Problem> {
+        let names: HashMap<u32, &Option<String>> = procs.iter().map(|p| (p.pid, &p.name)).collect();
+        let parent_name = |ppid: Option<u32>| {
+            ppid.and_then(|ppid| names.get(&ppid))
+                .and_then(|name| (*name).clone())
+        };
+
+        let mut ret: Vec<Problem> = Vec::new();
+
+        let alive: HashSet<(u32, u64)> = procs.iter().map(|p| (p.pid, p.start_time)).collect();
+        let disk_sleeping: HashSet<(u32, u64)> = procs
+            .iter()
+            .filter(|p| p.state == RunState::DiskSleep)
+            .map(|p| (p.pid, p.start_time))
+            .collect();
+        self.first_ppid.retain(|key, _| alive.contains(key));
+        self.disk_sleep_since
+            .retain(|key, _| disk_sleeping.contains(key));
+
+        for proc in procs {
+            let key = (proc.pid, proc.start_time);
+
+            let first_ppid = *self.first_ppid.entry(key).or_insert(proc.ppid);
+            if let Some(original_ppid) = first_ppid {
+                if proc.ppid != first_ppid {
+                    ret.push(Problem::Reparented {
+                        pid: proc.pid,
+                        name: proc.name.clone(),
+                        original_ppid,
+                        ppid: proc.ppid,
+                        parent_name: parent_name(proc.ppid),
+                    });
+                }
+            }
+
+            match proc.state {
+                RunState::Zombie => ret.push(Problem::Zombie {
+                    pid: proc.pid,
+                    name: proc.name.clone(),
+                    ppid: proc.ppid,
+                    parent_name: parent_name(proc.ppid),
+                }),
+                RunState::DiskSleep => {
+                    let since =
+                        now.duration_since(*self.disk_sleep_since.entry(key).or_insert(now));
+                    if since >= disk_sleep_threshold {
+                        ret.push(Problem::StuckDiskSleep {
+                            pid: proc.pid,
+                            name: proc.name.clone(),
+                            since,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proc(pid: u32, ppid: u32, state: RunState) -> Proc {
+        Proc {
+            pid,
+            ppid: Some(ppid),
+            name: Some(format!("proc{}", pid)),
+            start_time: 100,
+            state,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_zombie_reports_parent() {
+        let mut tracker = ProblemTracker::new();
+        let procs = vec![
+            proc(10, 1, RunState::Sleeping),
+            proc(11, 10, RunState::Zombie),
+        ];
+
+        let problems = tracker.update(&procs, Instant::now(), Duration::from_secs(10));
+
+        assert_eq!(problems.len(), 1);
+        match &problems[0] {
+            Problem::Zombie {
+                pid, parent_name, ..
+            } => {
+                assert_eq!(*pid, 11);
+                assert_eq!(parent_name.as_deref(), Some("proc10"));
+            }
+            _ => panic!("expected a zombie"),
+        }
+        assert_eq!(problems[0].actionable_pid(), 10);
+    }
+
+    #[test]
+    fn test_reparented_process_is_reported() {
+        let mut tracker = ProblemTracker::new();
+        let now = Instant::now();
+
+        tracker.update(
+            &[
+                proc(10, 1, RunState::Sleeping),
+                proc(11, 10, RunState::Sleeping),
+            ],
+            now,
+            Duration::from_secs(10),
+        );
+        let problems = tracker.update(
+            &[proc(11, 1, RunState::Sleeping)],
+            now,
+            Duration::from_secs(10),
+        );
+
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(
+            problems[0],
+            Problem::Reparented {
+                pid: 11,
+                original_ppid: 10,
+                ppid: Some(1),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_disk_sleep_only_reported_after_threshold() {
+        let mut tracker = ProblemTracker::new();
+        let start = Instant::now();
+        let procs = vec![proc(20, 1, RunState::DiskSleep)];
+        let threshold = Duration::from_secs(10);
+
+        assert!(tracker.update(&procs, start, threshold).is_empty());
+        assert!(tracker
+            .update(&procs, start + Duration::from_secs(5), threshold)
+            .is_empty());
+
+        let problems = tracker.update(&procs, start + Duration::from_secs(12), threshold);
+        assert!(matches!(
+            problems[0],
+            Problem::StuckDiskSleep { pid: 20, since, .. } if since == Duration::from_secs(12)
+        ));
+
+        // leaving disk sleep resets the clock
+        tracker.update(
+            &[proc(20, 1, RunState::Sleeping)],
+            start + Duration::from_secs(13),
+            threshold,
+        );
+        assert!(tracker
+            .update(&procs, start + Duration::from_secs(14), threshold)
+            .is_empty());
+    }
+}
@@ -1,5 +1,86 @@
-use sysinfo::{DiskUsage, System, Users};
+use std::fmt::{self, Display};
+
+use sysinfo::{DiskUsage, ProcessStatus, System, Users};
+
+use crate::{
+    cgroup::ProcCgroup,
+    namespace::Namespaces,
+    proc_mem::{MemoryMetric, ProcMemory},
+};
+
+/// the scheduler state of a process, collapsed from `sysinfo::ProcessStatus` into the states
+/// that matter on the platforms chadtop runs on
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RunState {
+    Running,
+    Sleeping,
+    /// uninterruptible sleep, `D` in `ps`
+    DiskSleep,
+    Zombie,
+    Stopped,
+    Traced,
+    Idle,
+    Dead,
+    #[default]
+    Unknown,
+}
+
+impl Display for RunState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            RunState::Running => "run",
+            RunState::Sleeping => "sleep",
+            RunState::DiskSleep => "disk",
+            RunState::Zombie => "zombie",
+            RunState::Stopped => "stop",
+            RunState::Traced => "trace",
+            RunState::Idle => "idle",
+            RunState::Dead => "dead",
+            RunState::Unknown => "?",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl From<ProcessStatus> for RunState {
+    fn from(status: ProcessStatus) -> Self {
+        match status {
+            ProcessStatus::Run | ProcessStatus::Waking => RunState::Running,
+            ProcessStatus::Sleep | ProcessStatus::Parked | ProcessStatus::LockBlocked => {
+                RunState::Sleeping
+            }
+            ProcessStatus::UninterruptibleDiskSleep | ProcessStatus::Wakekill => {
+                RunState::DiskSleep
+            }
+            ProcessStatus::Zombie => RunState::Zombie,
+            ProcessStatus::Stop => RunState::Stopped,
+            ProcessStatus::Tracing => RunState::Traced,
+            ProcessStatus::Idle => RunState::Idle,
+            ProcessStatus::Dead => RunState::Dead,
+            ProcessStatus::Unknown(_) => RunState::Unknown,
+        }
+    }
+}
+
+impl RunState {
+    /// parses either the name chadtop shows (i.e. `zombie`) or the letter `ps` shows (i.e. `Z`)
+    pub fn parse(s: &str) -> Option<RunState> {
+        match s {
+            "run" | "running" | "R" => Some(RunState::Running),
+            "sleep" | "sleeping" | "S" => Some(RunState::Sleeping),
+            "disk" | "D" => Some(RunState::DiskSleep),
+            "zombie" | "Z" => Some(RunState::Zombie),
+            "stop" | "stopped" | "T" => Some(RunState::Stopped),
+            "trace" | "traced" | "t" => Some(RunState::Traced),
+            "idle" | "I" => Some(RunState::Idle),
+            "dead" | "X" => Some(RunState::Dead),
+            _ => None,
+        }
+    }
+}
 
+#[derive(Clone, Default)]
 pub struct Proc {
     pub name: Option<String>,
     pub cmd: Option<String>,
@@ -11,11 +92,45 @@ pub struct Proc {
     pub start_time: u64,
     pub run_time: u64,
     pub cpu_usage: f32,
+    pub state: RunState,
     pub disk_usage_read: u64,
     pub disk_usage_written: u64,
     pub user: Option<String>,
     pub open_files: Option<usize>,
     pub open_files_limit: Option<usize>,
+    /// received bytes per second, only known for processes in their own network namespace
+    pub net_rx: Option<u64>,
+    /// transmitted bytes per second, only known for processes in their own network namespace
+    pub net_tx: Option<u64>,
+    /// only read when needed since `smaps_rollup` is expensive, see `State::refresh_procs`
+    pub memory_detail: Option<ProcMemory>,
+    /// filled in by `SysinfoSource::read_procs`, `None` when /proc/<pid>/cgroup can't be read
+    pub cgroup: Option<ProcCgroup>,
+    /// filled in by `SysinfoSource::read_procs`
+    pub namespaces: Namespaces,
+    /// the pid in every pid namespace the process is visible in, outermost first, filled in by
+    /// `SysinfoSource::read_procs`
+    pub ns_pids: Vec<u32>,
+}
+
+impl Proc {
+    /// the memory of this process according to `metric`, rss comes from sysinfo so it's always
+    /// available while the others need `memory_detail` to be populated
+    pub fn memory_by(&self, metric: MemoryMetric) -> Option<u64> {
+        match metric {
+            MemoryMetric::Rss => Some(self.memory),
+            _ => self.memory_detail.as_ref().and_then(|m| m.get(metric)),
+        }
+    }
+
+    /// the pid the process sees itself as, i.e. inside of a container. `None` when it isn't in
+    /// a nested pid namespace
+    pub fn inner_pid(&self) -> Option<u32> {
+        match self.ns_pids.as_slice() {
+            [_, .., inner] => Some(*inner),
+            _ => None,
+        }
+    }
 }
 
 /// read procs
@@ -57,11 +172,18 @@ pub fn read_procs(sys: &mut System, users: &mut Users) -> Vec<Proc> {
             start_time: proc.start_time(),
             run_time: proc.run_time(),
             cpu_usage: proc.cpu_usage(),
+            state: RunState::from(proc.status()),
             disk_usage_read: disk_usage.total_read_bytes,
             disk_usage_written: disk_usage.total_written_bytes,
             user,
             open_files: proc.open_files(),
             open_files_limit: proc.open_files_limit(),
+            net_rx: None,
+            net_tx: None,
+            memory_detail: None,
+            cgroup: None,
+            namespaces: Namespaces::default(),
+            ns_pids: Vec::new(),
         })
     }
 
@@ -85,6 +207,14 @@ mod tests {
         assert!(!procs.is_empty());
     }
 
+    #[test]
+    fn test_run_state_parse() {
+        assert_eq!(RunState::parse("zombie"), Some(RunState::Zombie));
+        assert_eq!(RunState::parse("D"), Some(RunState::DiskSleep));
+        assert_eq!(RunState::parse("t"), Some(RunState::Traced));
+        assert_eq!(RunState::parse("zom"), None);
+    }
+
     #[test]
     fn test_procs_have_usernames_when_possible() {
         let mut sys = System::new_all();
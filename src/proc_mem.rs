@@ -0,0 +1,196 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+};
+
+/// which number is used for a process' memory in the process table and when sorting by memory
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryMetric {
+    Rss,
+    Pss,
+    Uss,
+    Shared,
+    Swap,
+    VmPeak,
+    VmHwm,
+}
+
+impl Display for MemoryMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            MemoryMetric::Rss => "rss",
+            MemoryMetric::Pss => "pss",
+            MemoryMetric::Uss => "uss",
+            MemoryMetric::Shared => "shared",
+            MemoryMetric::Swap => "swap",
+            MemoryMetric::VmPeak => "vm peak",
+            MemoryMetric::VmHwm => "vm hwm",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl MemoryMetric {
+    pub fn next(&self) -> MemoryMetric {
+        match self {
+            MemoryMetric::Rss => MemoryMetric::Pss,
+            MemoryMetric::Pss => MemoryMetric::Uss,
+            MemoryMetric::Uss => MemoryMetric::Shared,
+            MemoryMetric::Shared => MemoryMetric::Swap,
+            MemoryMetric::Swap => MemoryMetric::VmPeak,
+            MemoryMetric::VmPeak => MemoryMetric::VmHwm,
+            MemoryMetric::VmHwm => MemoryMetric::Rss,
+        }
+    }
+}
+
+/// detailed memory numbers of a single process in bytes, any of these can be missing if the
+/// kernel doesn't expose it or we aren't allowed to read it
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct ProcMemory {
+    pub rss: Option<u64>,
+    /// proportional set size, shared pages are split evenly between the processes mapping them
+    pub pss: Option<u64>,
+    /// unique set size, memory that would be freed if the process exited
+    pub uss: Option<u64>,
+    pub shared: Option<u64>,
+    pub swap: Option<u64>,
+    pub vm_peak: Option<u64>,
+    pub vm_hwm: Option<u64>,
+}
+
+impl ProcMemory {
+    pub fn get(&self, metric: MemoryMetric) -> Option<u64> {
+        match metric {
+            MemoryMetric::Rss => self.rss,
+            MemoryMetric::Pss => self.pss,
+            MemoryMetric::Uss => self.uss,
+            MemoryMetric::Shared => self.shared,
+            MemoryMetric::Swap => self.swap,
+            MemoryMetric::VmPeak => self.vm_peak,
+            MemoryMetric::VmHwm => self.vm_hwm,
+        }
+    }
+}
+
+/// reads `/proc/<pid>/smaps_rollup` and `/proc/<pid>/status`
+///
+/// returns `None` on platforms without `/proc` or when both files are unreadable
+pub fn read_proc_memory(pid: u32) -> Option<ProcMemory> {
+    read_proc_memory_from(&Path::new("/proc").join(pid.to_string()))
+}
+
+/// same as `read_proc_memory` but reads from a `/proc/<pid>` style directory
+pub fn read_proc_memory_from(pid_root: &Path) -> Option<ProcMemory> {
+    let smaps = fs::read_to_string(pid_root.join("smaps_rollup")).ok();
+    let status = fs::read_to_string(pid_root.join("status")).ok();
+
+    if smaps.is_none() && status.is_none() {
+        return None;
+    }
+
+    let mut ret = ProcMemory::default();
+
+    if let Some(smaps) = smaps {
+        let shared_clean = kb_field(&smaps, "Shared_Clean:");
+        let shared_dirty = kb_field(&smaps, "Shared_Dirty:");
+        let private_clean = kb_field(&smaps, "Private_Clean:");
+        let private_dirty = kb_field(&smaps, "Private_Dirty:");
+
+        ret.rss = kb_field(&smaps, "Rss:");
+        ret.pss = kb_field(&smaps, "Pss:");
+        ret.swap = kb_field(&smaps, "Swap:");
+        ret.shared = add_options(shared_clean, shared_dirty);
+        ret.uss = add_options(private_clean, private_dirty);
+    }
+
+    if let Some(status) = status {
+        ret.vm_peak = kb_field(&status, "VmPeak:");
+        ret.vm_hwm = kb_field(&status, "VmHWM:");
+        // kernel threads and processes without smaps_rollup (pre 4.14) still have these
+        ret.rss = ret.rss.or(kb_field(&status, "VmRSS:"));
+        ret.swap = ret.swap.or(kb_field(&status, "VmSwap:"));
+    }
+
+    Some(ret)
+}
+
+/// finds a `Key:   1234 kB` line and returns the value in bytes
+pub(crate) fn kb_field(content: &str, key: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix(key))
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+fn add_options(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (None, None) => None,
+        _ => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_read_proc_memory_from_fixture() {
+        let root = temp_path("procmem");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        fs::write(
+            root.join("smaps_rollup"),
+            "55d4c0a00000-7ffd5b5fe000 ---p 00000000 00:00 0                          [rollup]
+Rss:                1000 kB
+Pss:                 600 kB
+Pss_Anon:            300 kB
+Shared_Clean:        500 kB
+Shared_Dirty:          0 kB
+Private_Clean:       100 kB
+Private_Dirty:       400 kB
+Swap:                 20 kB
+",
+        )
+        .unwrap();
+        fs::write(
+            root.join("status"),
+            "Name:\tworker\nVmPeak:\t    9000 kB\nVmHWM:\t    1200 kB\nVmRSS:\t    1000 kB\n",
+        )
+        .unwrap();
+
+        let mem = read_proc_memory_from(&root).expect("expected memory info");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(mem.rss, Some(1000 * 1024));
+        assert_eq!(mem.pss, Some(600 * 1024));
+        assert_eq!(mem.uss, Some(500 * 1024));
+        assert_eq!(mem.shared, Some(500 * 1024));
+        assert_eq!(mem.swap, Some(20 * 1024));
+        assert_eq!(mem.vm_peak, Some(9000 * 1024));
+        assert_eq!(mem.vm_hwm, Some(1200 * 1024));
+    }
+
+    #[test]
+    fn test_read_proc_memory_from_missing_dir() {
+        assert_eq!(
+            read_proc_memory_from(Path::new("/definitely/not/a/proc/dir")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_memory_metric_cycles_back_to_rss() {
+        let mut metric = MemoryMetric::Rss;
+        for _ in 0..7 {
+            metric = metric.next();
+        }
+        assert_eq!(metric, MemoryMetric::Rss);
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::cgroup::{cgroup2_root, read_cgroup_from};
+
+/// how many samples of avg10 are kept for the sparklines
+pub const PSI_HISTORY_CAPACITY: usize = 120;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PsiResource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+impl Display for PsiResource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            PsiResource::Cpu => "cpu",
+            PsiResource::Memory => "memory",
+            PsiResource::Io => "io",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
+impl PsiResource {
+    pub const ALL: [PsiResource; 3] = [PsiResource::Cpu, PsiResource::Memory, PsiResource::Io];
+}
+
+/// one line of a pressure file, the averages are the percentage of time tasks were stalled
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PressureLine {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    /// microseconds stalled since boot
+    pub total: u64,
+}
+
+/// a parsed pressure file
+///
+/// `some` is time at least one task was stalled, `full` is time every non-idle task was stalled
+/// at once. Older kernels have no `full` line for cpu
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pressure {
+    pub some: PressureLine,
+    pub full: Option<PressureLine>,
+}
+
+/// pressure of every resource, a resource is `None` when its file can't be read
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PressureSet {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+impl PressureSet {
+    pub fn get(&self, resource: PsiResource) -> Option<Pressure> {
+        match resource {
+            PsiResource::Cpu => self.cpu,
+            PsiResource::Memory => self.memory,
+            PsiResource::Io => self.io,
+        }
+    }
+
+    /// false on kernels built without psi or booted with psi=0
+    pub fn is_available(&self) -> bool {
+        self.cpu.is_some() || self.memory.is_some() || self.io.is_some()
+    }
+}
+
+/// reads /proc/pressure/{cpu,memory,io}
+pub fn read_pressure() -> PressureSet {
+    read_pressure_from(Path::new("/proc/pressure"))
+}
+
+pub fn read_pressure_from(pressure_root: &Path) -> PressureSet {
+    let read = |name: &str| read_pressure_file(&pressure_root.join(name));
+
+    PressureSet {
+        cpu: read("cpu"),
+        memory: read("memory"),
+        io: read("io"),
+    }
+}
+
+/// reads the `*.pressure` files of the cgroup v2 `pid` lives in, `None` when the process isn't in
+/// a v2 cgroup or the files can't be read
+pub fn read_cgroup_pressure(pid: u32) -> Option<(String, PressureSet)> {
+    read_cgroup_pressure_from(&Path::new("/proc").join(pid.to_string()), &cgroup2_root()?)
+}
+
+pub fn read_cgroup_pressure_from(
+    pid_root: &Path,
+    cgroup_root: &Path,
+) -> Option<(String, PressureSet)> {
+    let path = read_cgroup_from(pid_root)?.v2_path?;
+
+    let dir: PathBuf = cgroup_root.join(path.trim_start_matches('/'));
+    let read = |name: &str| read_pressure_file(&dir.join(format!("{}.pressure", name)));
+
+    let set = PressureSet {
+        cpu: read("cpu"),
+        memory: read("memory"),
+        io: read("io"),
+    };
+
+    set.is_available().then_some((path, set))
+}
+
+fn read_pressure_file(path: &Path) -> Option<Pressure> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| parse_pressure(&content))
+}
+
+/// parses
+/// ```text
+/// some avg10=0.12 avg60=0.05 avg300=0.01 total=123456
+/// full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+/// ```
+fn parse_pressure(content: &str) -> Option<Pressure> {
+    let mut some: Option<PressureLine> = None;
+    let mut full: Option<PressureLine> = None;
+
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+
+        let mut parsed = PressureLine::default();
+        for field in fields {
+            match field.split_once('=') {
+                Some(("avg10", v)) => parsed.avg10 = v.parse().ok()?,
+                Some(("avg60", v)) => parsed.avg60 = v.parse().ok()?,
+                Some(("avg300", v)) => parsed.avg300 = v.parse().ok()?,
+                Some(("total", v)) => parsed.total = v.parse().ok()?,
+                _ => {}
+            }
+        }
+
+        match kind {
+            Some("some") => some = Some(parsed),
+            Some("full") => full = Some(parsed),
+            _ => {}
+        }
+    }
+
+    Some(Pressure { some: some?, full })
+}
+
+/// the last `PSI_HISTORY_CAPACITY` `some avg10` values of every resource
+///
+/// stored as hundredths of a percent so they can go straight into a sparkline
+#[derive(Default)]
+pub struct PsiHistory {
+    pub cpu: VecDeque<u64>,
+    pub memory: VecDeque<u64>,
+    pub io: VecDeque<u64>,
+}
+
+impl PsiHistory {
+    pub fn new() -> PsiHistory {
+        PsiHistory::default()
+    }
+
+    pub fn push(&mut self, set: &PressureSet) {
+        for resource in PsiResource::ALL {
+            if let Some(pressure) = set.get(resource) {
+                let history = self.get_mut(resource);
+                if history.len() >= PSI_HISTORY_CAPACITY {
+                    history.pop_front();
+                }
+                history.push_back((pressure.some.avg10 * 100.0) as u64);
+            }
+        }
+    }
+
+    pub fn get(&self, resource: PsiResource) -> &VecDeque<u64> {
+        match resource {
+            PsiResource::Cpu => &self.cpu,
+            PsiResource::Memory => &self.memory,
+            PsiResource::Io => &self.io,
+        }
+    }
+
+    fn get_mut(&mut self, resource: PsiResource) -> &mut VecDeque<u64> {
+        match resource {
+            PsiResource::Cpu => &mut self.cpu,
+            PsiResource::Memory => &mut self.memory,
+            PsiResource::Io => &mut self.io,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{temp_path, write_file};
+
+    const MEMORY: &str = "some avg10=1.50 avg60=0.75 avg300=0.10 total=123456
+full avg10=0.50 avg60=0.25 avg300=0.00 total=6543
+";
+
+    #[test]
+    fn test_parse_pressure() {
+        let pressure = parse_pressure(MEMORY).unwrap();
+
+        assert_eq!(pressure.some.avg10, 1.5);
+        assert_eq!(pressure.some.total, 123456);
+        assert_eq!(pressure.full.map(|f| f.avg60), Some(0.25));
+
+        let cpu_without_full = parse_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n");
+        assert_eq!(cpu_without_full.unwrap().full, None);
+        assert_eq!(parse_pressure(""), None);
+    }
+
+    #[test]
+    fn test_read_pressure_missing_is_unavailable() {
+        let root = temp_path("psi_missing");
+        write_file(&root.join("memory"), MEMORY);
+
+        let partial = read_pressure_from(&root);
+        let missing = read_pressure_from(&root.join("nope"));
+
+        fs::remove_dir_all(&root).unwrap();
+        assert!(partial.is_available());
+        assert_eq!(partial.cpu, None);
+        assert!(!missing.is_available());
+    }
+
+    #[test]
+    fn test_read_cgroup_pressure_from_fixture() {
+        let root = temp_path("psi_cgroup");
+        let pid_root = root.join("proc/42");
+        write_file(
+            &pid_root.join("cgroup"),
+            "1:name=systemd:/\n0::/system.slice/foo.service\n",
+        );
+        write_file(
+            &root.join("cgroup/system.slice/foo.service/memory.pressure"),
+            MEMORY,
+        );
+
+        let (path, set) = read_cgroup_pressure_from(&pid_root, &root.join("cgroup")).unwrap();
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(path, "/system.slice/foo.service");
+        assert_eq!(set.memory.map(|m| m.some.avg10), Some(1.5));
+        assert_eq!(set.io, None);
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = PsiHistory::new();
+        let set = PressureSet {
+            memory: parse_pressure(MEMORY),
+            ..Default::default()
+        };
+
+        for _ in 0..PSI_HISTORY_CAPACITY + 5 {
+            history.push(&set);
+        }
+
+        assert_eq!(history.memory.len(), PSI_HISTORY_CAPACITY);
+        assert_eq!(history.memory.back(), Some(&150));
+        assert!(history.cpu.is_empty());
+    }
+}
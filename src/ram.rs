@@ -1,5 +1,6 @@
 use sysinfo::System;
 
+#[derive(Clone, Copy)]
 pub struct Ram {
     pub total: u64,
     pub free: u64,
@@ -10,6 +11,12 @@ pub struct Ram {
     pub used_swap: u64,
 }
 
+impl Default for Ram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Ram {
     pub fn new() -> Ram {
         Ram {
@@ -0,0 +1,782 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cpu::CpuUsage,
+    cpu_time::CpuBreakdown,
+    info::{Info, Status},
+    namespace::Namespaces,
+    proc::{Proc, RunState},
+    ram::Ram,
+};
+
+/// first bytes of every recording, bumped whenever the frame layout changes
+const MAGIC: &[u8; 8] = b"CHADREC1";
+
+/// everything chadtop shows about the system at one refresh
+///
+/// processes don't keep their cgroup, namespaces or memory detail, those are read separately
+/// and would make every frame several times bigger
+pub struct Snapshot {
+    /// seconds since the unix epoch
+    pub time: u64,
+    pub processes: Vec<Proc>,
+    pub cpus: Vec<CpuUsage>,
+    pub ram: Ram,
+    pub info: Info,
+    pub status: Status,
+}
+
+/// appends a frame per refresh to a recording
+///
+/// frames are a varint length followed by the encoded snapshot so a recording cut short by a
+/// crash is only missing its last frame
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    /// starts a recording at `path`, appending to it when it already is one
+    ///
+    /// a last frame cut short by a crash is cut off first, frames appended after it would
+    /// otherwise be read as part of it
+    pub fn create(path: &Path) -> io::Result<Recorder> {
+        let existing = fs::metadata(path).map(|m| m.len() > 0).unwrap_or(false);
+        let end = if existing {
+            Some(Recording::open(path)?.end())
+        } else {
+            None
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        match end {
+            Some(end) => file.set_len(end as u64)?,
+            None => file.write_all(MAGIC)?,
+        }
+
+        Ok(Recorder { file })
+    }
+
+    pub fn write(
+        &mut self,
+        time: u64,
+        processes: &[Proc],
+        cpus: &[CpuUsage],
+        ram: &Ram,
+        info: &Info,
+        status: &Status,
+    ) -> io::Result<()> {
+        let mut payload = Encoder::default();
+        payload.u64(time);
+        encode_procs(&mut payload, processes);
+        encode_cpus(&mut payload, cpus);
+        encode_ram(&mut payload, ram);
+        encode_info(&mut payload, info);
+        encode_status(&mut payload, status);
+
+        let mut frame = Encoder::default();
+        frame.u64(payload.buf.len() as u64);
+        frame.buf.extend_from_slice(&payload.buf);
+
+        // one write per frame so a frame is never interleaved with a partial one
+        self.file.write_all(&frame.buf)
+    }
+}
+
+/// a recording read back for replaying, frames are only decoded when they are shown
+pub struct Recording {
+    data: Vec<u8>,
+    /// start of every frame's payload and its length
+    frames: Vec<(usize, usize)>,
+    /// time of every frame
+    times: Vec<u64>,
+}
+
+impl Recording {
+    pub fn open(path: &Path) -> io::Result<Recording> {
+        Recording::parse(fs::read(path)?)
+    }
+
+    pub fn parse(data: Vec<u8>) -> io::Result<Recording> {
+        if !data.starts_with(MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a chadtop recording",
+            ));
+        }
+
+        let mut frames = Vec::new();
+        let mut times = Vec::new();
+        let mut decoder = Decoder {
+            data: &data,
+            pos: MAGIC.len(),
+        };
+
+        // a truncated last frame is dropped
+        while let Some(len) = decoder.u64() {
+            let start = decoder.pos;
+            let end = start.saturating_add(len as usize);
+            if end > data.len() {
+                break;
+            }
+
+            let time = Decoder {
+                data: &data[start..end],
+                pos: 0,
+            }
+            .u64();
+            match time {
+                Some(time) => times.push(time),
+                None => break,
+            }
+
+            frames.push((start, len as usize));
+            decoder.pos = end;
+        }
+
+        Ok(Recording {
+            data,
+            frames,
+            times,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// where the last complete frame ends
+    fn end(&self) -> usize {
+        self.frames
+            .last()
+            .map(|(start, len)| start + len)
+            .unwrap_or(MAGIC.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// seconds since the unix epoch of frame `idx`
+    pub fn time(&self, idx: usize) -> Option<u64> {
+        self.times.get(idx).copied()
+    }
+
+    /// the last frame at or before `time`, the first one when `time` is before all of them
+    pub fn frame_at(&self, time: u64) -> usize {
+        self.times.partition_point(|t| *t <= time).saturating_sub(1)
+    }
+
+    pub fn frame(&self, idx: usize) -> Option<Snapshot> {
+        let (start, len) = *self.frames.get(idx)?;
+        let mut d = Decoder {
+            data: &self.data[start..start + len],
+            pos: 0,
+        };
+
+        Some(Snapshot {
+            time: d.u64()?,
+            processes: decode_procs(&mut d)?,
+            cpus: decode_cpus(&mut d)?,
+            ram: decode_ram(&mut d)?,
+            info: decode_info(&mut d)?,
+            status: decode_status(&mut d)?,
+        })
+    }
+}
+
+/// replay speeds `-` and `+` step through
+const SPEEDS: [f64; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+
+/// where a replay is and how fast it moves, the recorded time advances by `speed` seconds per
+/// second while playing
+pub struct Replay {
+    pub recording: Recording,
+    pub frame: usize,
+    pub paused: bool,
+    pub speed: f64,
+    /// the recorded time being shown, between frames when playing slower than recorded
+    cursor: f64,
+    last_advance: Instant,
+}
+
+impl Replay {
+    pub fn new(recording: Recording) -> Replay {
+        Replay {
+            cursor: recording.time(0).unwrap_or(0) as f64,
+            recording,
+            frame: 0,
+            paused: false,
+            speed: 1.0,
+            last_advance: Instant::now(),
+        }
+    }
+
+    /// seconds since the unix epoch of the frame being shown
+    pub fn time(&self) -> Option<u64> {
+        self.recording.time(self.frame)
+    }
+
+    pub fn snapshot(&self) -> Option<Snapshot> {
+        self.recording.frame(self.frame)
+    }
+
+    /// moves the cursor by the time passed since the last call, pausing at the end
+    pub fn advance(&mut self) {
+        let elapsed = self.last_advance.elapsed();
+        self.advance_by(elapsed);
+    }
+
+    fn advance_by(&mut self, elapsed: Duration) {
+        self.last_advance = Instant::now();
+        if self.paused {
+            return;
+        }
+
+        self.cursor += elapsed.as_secs_f64() * self.speed;
+        self.frame = self.recording.frame_at(self.cursor as u64);
+        if self.frame + 1 >= self.recording.len() {
+            self.paused = true;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        // playing from the end starts over
+        if !self.paused && self.frame + 1 >= self.recording.len() {
+            self.go_to(0);
+        }
+        self.last_advance = Instant::now();
+    }
+
+    /// one frame forward or back, pausing the replay
+    pub fn step(&mut self, forward: bool) {
+        self.paused = true;
+        let frame = if forward {
+            (self.frame + 1).min(self.recording.len().saturating_sub(1))
+        } else {
+            self.frame.saturating_sub(1)
+        };
+        self.go_to(frame);
+    }
+
+    /// moves `secs` of recorded time forward or back
+    pub fn seek(&mut self, secs: i64) {
+        let time = self.time().unwrap_or(0).saturating_add_signed(secs);
+        self.go_to(self.recording.frame_at(time));
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = SPEEDS
+            .into_iter()
+            .find(|s| *s > self.speed)
+            .unwrap_or(self.speed);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = SPEEDS
+            .into_iter()
+            .rev()
+            .find(|s| *s < self.speed)
+            .unwrap_or(self.speed);
+    }
+
+    fn go_to(&mut self, frame: usize) {
+        self.frame = frame;
+        self.cursor = self.time().unwrap_or(0) as f64;
+    }
+}
+
+/// integers are leb128 varints since most of them are small, floats are little endian
+#[derive(Default)]
+struct Encoder {
+    buf: Vec<u8>,
+}
+
+impl Encoder {
+    fn u64(&mut self, mut v: u64) {
+        loop {
+            let byte = (v & 0x7f) as u8;
+            v >>= 7;
+            if v == 0 {
+                self.buf.push(byte);
+                return;
+            }
+            self.buf.push(byte | 0x80);
+        }
+    }
+
+    fn f32(&mut self, v: f32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn f64(&mut self, v: f64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.u64(s.len() as u64);
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    fn opt_str(&mut self, s: Option<&str>) {
+        match s {
+            Some(s) => {
+                self.buf.push(1);
+                self.str(s);
+            }
+            None => self.buf.push(0),
+        }
+    }
+
+    fn opt_u64(&mut self, v: Option<u64>) {
+        match v {
+            Some(v) => {
+                self.buf.push(1);
+                self.u64(v);
+            }
+            None => self.buf.push(0),
+        }
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Decoder<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let b = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let mut v: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Some(v);
+            }
+        }
+        None
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.data.get(self.pos..self.pos + N)?.try_into().ok()?;
+        self.pos += N;
+        Some(bytes)
+    }
+
+    fn f32(&mut self) -> Option<f32> {
+        self.bytes().map(f32::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Option<f64> {
+        self.bytes().map(f64::from_le_bytes)
+    }
+
+    fn str(&mut self) -> Option<String> {
+        let len = self.u64()? as usize;
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+
+    fn opt_str(&mut self) -> Option<Option<String>> {
+        match self.byte()? {
+            0 => Some(None),
+            _ => self.str().map(Some),
+        }
+    }
+
+    fn opt_u64(&mut self) -> Option<Option<u64>> {
+        match self.byte()? {
+            0 => Some(None),
+            _ => self.u64().map(Some),
+        }
+    }
+}
+
+const RUN_STATES: [RunState; 9] = [
+    RunState::Running,
+    RunState::Sleeping,
+    RunState::DiskSleep,
+    RunState::Zombie,
+    RunState::Stopped,
+    RunState::Traced,
+    RunState::Idle,
+    RunState::Dead,
+    RunState::Unknown,
+];
+
+fn encode_procs(e: &mut Encoder, processes: &[Proc]) {
+    e.u64(processes.len() as u64);
+
+    for p in processes {
+        e.u64(p.pid as u64);
+        e.opt_u64(p.ppid.map(u64::from));
+        e.opt_str(p.name.as_deref());
+        e.opt_str(p.cmd.as_deref());
+        e.opt_str(p.exe.as_deref());
+        e.opt_str(p.cwd.as_deref());
+        e.opt_str(p.user.as_deref());
+        e.u64(p.memory);
+        e.u64(p.start_time);
+        e.u64(p.run_time);
+        e.f32(p.cpu_usage);
+        e.u64(RUN_STATES.iter().position(|s| *s == p.state).unwrap_or(8) as u64);
+        e.u64(p.disk_usage_read);
+        e.u64(p.disk_usage_written);
+        e.opt_u64(p.open_files.map(|n| n as u64));
+        e.opt_u64(p.open_files_limit.map(|n| n as u64));
+        e.opt_u64(p.net_rx);
+        e.opt_u64(p.net_tx);
+    }
+}
+
+fn decode_procs(d: &mut Decoder) -> Option<Vec<Proc>> {
+    let len = d.u64()? as usize;
+    // the length comes from the file so don't trust it for the allocation
+    let mut ret = Vec::with_capacity(len.min(1 << 16));
+
+    for _ in 0..len {
+        ret.push(Proc {
+            pid: d.u64()?.try_into().ok()?,
+            ppid: d.opt_u64()?.and_then(|p| p.try_into().ok()),
+            name: d.opt_str()?,
+            cmd: d.opt_str()?,
+            exe: d.opt_str()?,
+            cwd: d.opt_str()?,
+            user: d.opt_str()?,
+            memory: d.u64()?,
+            start_time: d.u64()?,
+            run_time: d.u64()?,
+            cpu_usage: d.f32()?,
+            state: *RUN_STATES.get(d.u64()? as usize)?,
+            disk_usage_read: d.u64()?,
+            disk_usage_written: d.u64()?,
+            open_files: d.opt_u64()?.map(|n| n as usize),
+            open_files_limit: d.opt_u64()?.map(|n| n as usize),
+            net_rx: d.opt_u64()?,
+            net_tx: d.opt_u64()?,
+            memory_detail: None,
+            cgroup: None,
+            namespaces: Namespaces::default(),
+            ns_pids: Vec::new(),
+        });
+    }
+
+    Some(ret)
+}
+
+fn encode_cpus(e: &mut Encoder, cpus: &[CpuUsage]) {
+    e.u64(cpus.len() as u64);
+
+    for cpu in cpus {
+        e.str(&cpu.name);
+        e.f32(cpu.usage);
+        e.opt_u64(cpu.frequency);
+        match cpu.breakdown {
+            Some(b) => {
+                e.buf.push(1);
+                for v in [
+                    b.user, b.nice, b.system, b.idle, b.iowait, b.irq, b.softirq, b.steal,
+                ] {
+                    e.f32(v);
+                }
+            }
+            None => e.buf.push(0),
+        }
+    }
+}
+
+fn decode_cpus(d: &mut Decoder) -> Option<Vec<CpuUsage>> {
+    let len = d.u64()? as usize;
+    let mut ret = Vec::with_capacity(len.min(1 << 12));
+
+    for _ in 0..len {
+        ret.push(CpuUsage {
+            name: d.str()?,
+            usage: d.f32()?,
+            frequency: d.opt_u64()?,
+            breakdown: match d.byte()? {
+                0 => None,
+                _ => Some(CpuBreakdown {
+                    user: d.f32()?,
+                    nice: d.f32()?,
+                    system: d.f32()?,
+                    idle: d.f32()?,
+                    iowait: d.f32()?,
+                    irq: d.f32()?,
+                    softirq: d.f32()?,
+                    steal: d.f32()?,
+                }),
+            },
+        });
+    }
+
+    Some(ret)
+}
+
+fn encode_ram(e: &mut Encoder, ram: &Ram) {
+    for v in [
+        ram.total,
+        ram.free,
+        ram.available,
+        ram.used,
+        ram.total_swap,
+        ram.free_swap,
+        ram.used_swap,
+    ] {
+        e.u64(v);
+    }
+}
+
+fn decode_ram(d: &mut Decoder) -> Option<Ram> {
+    Some(Ram {
+        total: d.u64()?,
+        free: d.u64()?,
+        available: d.u64()?,
+        used: d.u64()?,
+        total_swap: d.u64()?,
+        free_swap: d.u64()?,
+        used_swap: d.u64()?,
+    })
+}
+
+fn encode_info(e: &mut Encoder, info: &Info) {
+    e.opt_str(info.long_os_version.as_deref());
+    e.str(&info.kernel_long_version);
+    e.opt_str(info.host_name.as_deref());
+    e.str(&info.cpu_arch);
+    e.opt_u64(info.physical_core_count.map(|n| n as u64));
+}
+
+fn decode_info(d: &mut Decoder) -> Option<Info> {
+    Some(Info {
+        long_os_version: d.opt_str()?,
+        kernel_long_version: d.str()?,
+        host_name: d.opt_str()?,
+        cpu_arch: d.str()?,
+        physical_core_count: d.opt_u64()?.map(|n| n as usize),
+    })
+}
+
+fn encode_status(e: &mut Encoder, status: &Status) {
+    e.f64(status.load_one);
+    e.f64(status.load_five);
+    e.f64(status.load_fifteen);
+    e.u64(status.uptime);
+    e.u64(status.boot_time);
+    e.u64(status.process_count as u64);
+    e.opt_u64(status.thread_count.map(|n| n as u64));
+}
+
+fn decode_status(d: &mut Decoder) -> Option<Status> {
+    Some(Status {
+        load_one: d.f64()?,
+        load_five: d.f64()?,
+        load_fifteen: d.f64()?,
+        uptime: d.u64()?,
+        boot_time: d.u64()?,
+        process_count: d.u64()? as usize,
+        thread_count: d.opt_u64()?.map(|n| n as usize),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    fn info() -> Info {
+        Info {
+            long_os_version: Some(String::from("Linux (Arch)")),
+            kernel_long_version: String::from("6.9"),
+            host_name: None,
+            cpu_arch: String::from("x86_64"),
+            physical_core_count: Some(4),
+        }
+    }
+
+    fn write_frame(recorder: &mut Recorder, time: u64, pid: u32) {
+        let procs = [Proc {
+            pid,
+            ppid: Some(1),
+            name: Some(String::from("postgres")),
+            cmd: Some(String::from("postgres -D /var/lib/pgsql")),
+            state: RunState::DiskSleep,
+            cpu_usage: 12.5,
+            memory: 1 << 30,
+            net_rx: Some(300),
+            ..Default::default()
+        }];
+        let cpus = [CpuUsage {
+            name: String::from("cpu0"),
+            usage: 50.0,
+            frequency: Some(3000),
+            breakdown: Some(CpuBreakdown {
+                user: 40.0,
+                ..Default::default()
+            }),
+        }];
+        let ram = Ram {
+            total: 16 << 30,
+            available: 8 << 30,
+            ..Ram::new()
+        };
+        let status = Status {
+            load_one: 1.5,
+            thread_count: Some(900),
+            ..Default::default()
+        };
+
+        recorder
+            .write(time, &procs, &cpus, &ram, &info(), &status)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_round_trip_and_append() {
+        let path = temp_path("record_round_trip");
+        let _ = fs::remove_file(&path);
+
+        write_frame(&mut Recorder::create(&path).unwrap(), 100, 42);
+        // a second session appends to the same recording
+        write_frame(&mut Recorder::create(&path).unwrap(), 105, 43);
+        let recording = Recording::open(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(recording.len(), 2);
+        assert_eq!(recording.time(1), Some(105));
+
+        let frame = recording.frame(0).unwrap();
+        let p = &frame.processes[0];
+        assert_eq!(frame.time, 100);
+        assert_eq!(p.pid, 42);
+        assert_eq!(p.cmd.as_deref(), Some("postgres -D /var/lib/pgsql"));
+        assert_eq!(p.state, RunState::DiskSleep);
+        assert_eq!(p.cpu_usage, 12.5);
+        assert_eq!(p.net_rx, Some(300));
+        assert_eq!(p.user, None);
+        assert_eq!(frame.cpus[0].breakdown.map(|b| b.user), Some(40.0));
+        assert_eq!(frame.ram.available, 8 << 30);
+        assert_eq!(frame.info.physical_core_count, Some(4));
+        assert_eq!(frame.status.thread_count, Some(900));
+        assert_eq!(recording.frame(1).unwrap().processes[0].pid, 43);
+    }
+
+    #[test]
+    fn test_truncated_recording_drops_last_frame() {
+        let path = temp_path("record_truncated");
+        let _ = fs::remove_file(&path);
+        let mut recorder = Recorder::create(&path).unwrap();
+        write_frame(&mut recorder, 100, 42);
+        write_frame(&mut recorder, 101, 42);
+
+        let mut data = fs::read(&path).unwrap();
+        data.truncate(data.len() - 10);
+        fs::remove_file(&path).unwrap();
+        let recording = Recording::parse(data).unwrap();
+
+        assert_eq!(recording.len(), 1);
+        assert!(Recording::parse(b"garbage".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_append_after_truncated_frame() {
+        let path = temp_path("record_append_truncated");
+        let _ = fs::remove_file(&path);
+        let mut recorder = Recorder::create(&path).unwrap();
+        write_frame(&mut recorder, 100, 42);
+        write_frame(&mut recorder, 101, 42);
+        drop(recorder);
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 10)
+            .unwrap();
+
+        write_frame(&mut Recorder::create(&path).unwrap(), 102, 43);
+        let recording = Recording::open(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(recording.len(), 2);
+        assert_eq!(recording.time(1), Some(102));
+        assert_eq!(recording.frame(1).unwrap().processes[0].pid, 43);
+    }
+
+    #[test]
+    fn test_frame_at() {
+        let path = temp_path("record_frame_at");
+        let _ = fs::remove_file(&path);
+        let mut recorder = Recorder::create(&path).unwrap();
+        for time in [100, 101, 103] {
+            write_frame(&mut recorder, time, 42);
+        }
+        let recording = Recording::open(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(recording.frame_at(50), 0);
+        assert_eq!(recording.frame_at(102), 1);
+        assert_eq!(recording.frame_at(200), 2);
+    }
+
+    #[test]
+    fn test_replay_controls() {
+        let path = temp_path("record_replay");
+        let _ = fs::remove_file(&path);
+        let mut recorder = Recorder::create(&path).unwrap();
+        for time in 100..110 {
+            write_frame(&mut recorder, time, time as u32);
+        }
+        let mut replay = Replay::new(Recording::open(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+
+        replay.advance_by(Duration::from_secs(2));
+        assert_eq!(replay.time(), Some(102));
+
+        replay.faster();
+        replay.advance_by(Duration::from_secs(2));
+        assert_eq!(replay.time(), Some(106));
+
+        replay.step(false);
+        assert!(replay.paused);
+        assert_eq!(replay.snapshot().unwrap().processes[0].pid, 105);
+        replay.advance_by(Duration::from_secs(2));
+        assert_eq!(replay.time(), Some(105));
+
+        replay.seek(-60);
+        assert_eq!(replay.frame, 0);
+        replay.seek(60);
+        assert_eq!(replay.frame, 9);
+
+        replay.toggle_pause();
+        assert_eq!(replay.frame, 0);
+        replay.slower();
+        replay.slower();
+        replay.advance_by(Duration::from_secs(1));
+        assert_eq!(replay.time(), Some(100));
+        assert_eq!(replay.speed, 0.5);
+    }
+
+    #[test]
+    fn test_refuses_to_append_to_other_files() {
+        let path = temp_path("record_other");
+        fs::write(&path, "hello").unwrap();
+
+        let recorder = Recorder::create(&path);
+
+        fs::remove_file(&path).unwrap();
+        assert!(recorder.is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sysinfo::Components;
+
+/// how many temperature samples are kept per sensor
+pub const SENSOR_HISTORY_CAPACITY: usize = 120;
+
+/// how close to critical (in °C) a sensor has to get before it counts as hot, used as the hot
+/// threshold on its own for sensors that don't report a critical temperature
+const HOT_MARGIN: f32 = 10.0;
+const HOT_WITHOUT_CRITICAL: f32 = 80.0;
+
+/// a temperature sensor at one point in time, all temperatures are in °C
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SensorReading {
+    /// index of the sensor in `Components`, labels aren't unique (i.e. `coretemp Core 0` on every
+    /// socket of a multi-socket box)
+    pub id: usize,
+    pub label: String,
+    pub temperature: Option<f32>,
+    /// highest temperature seen since the sensor was first read
+    pub max: Option<f32>,
+    /// temperature the hardware considers critical, usually where it starts shutting down
+    pub critical: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TempLevel {
+    Normal,
+    Hot,
+    Critical,
+}
+
+impl SensorReading {
+    pub fn level(&self) -> TempLevel {
+        let temperature = match self.temperature {
+            Some(t) => t,
+            None => return TempLevel::Normal,
+        };
+
+        match self.critical.filter(|c| *c > 0.0) {
+            Some(critical) if temperature >= critical => TempLevel::Critical,
+            Some(critical) if temperature >= critical - HOT_MARGIN => TempLevel::Hot,
+            None if temperature >= HOT_WITHOUT_CRITICAL => TempLevel::Hot,
+            _ => TempLevel::Normal,
+        }
+    }
+}
+
+/// before calling this refresh `components`
+pub fn read_sensors(components: &Components) -> Vec<SensorReading> {
+    let mut ret: Vec<SensorReading> = components
+        .list()
+        .iter()
+        .enumerate()
+        .map(|(id, c)| SensorReading {
+            id,
+            label: c.label().to_string(),
+            temperature: c.temperature().filter(|t| !t.is_nan()),
+            max: c.max().filter(|t| !t.is_nan()),
+            critical: c.critical().filter(|t| !t.is_nan()),
+        })
+        .collect();
+
+    ret.sort_by(|a, b| a.label.cmp(&b.label).then(a.id.cmp(&b.id)));
+    ret
+}
+
+/// the last `SENSOR_HISTORY_CAPACITY` temperatures of every sensor, keyed by `SensorReading::id`
+#[derive(Default)]
+pub struct SensorHistory {
+    history: HashMap<usize, VecDeque<f32>>,
+}
+
+impl SensorHistory {
+    pub fn new() -> SensorHistory {
+        SensorHistory::default()
+    }
+
+    /// records `readings`, sensors that disappeared (i.e. an unplugged drive) are forgotten
+    pub fn push(&mut self, readings: &[SensorReading]) {
+        let ids: HashSet<usize> = readings.iter().map(|r| r.id).collect();
+        self.history.retain(|id, _| ids.contains(id));
+
+        for reading in readings {
+            if let Some(temperature) = reading.temperature {
+                let history = self.history.entry(reading.id).or_default();
+                if history.len() >= SENSOR_HISTORY_CAPACITY {
+                    history.pop_front();
+                }
+                history.push_back(temperature);
+            }
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&VecDeque<f32>> {
+        self.history.get(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(id: usize, temperature: f32, critical: Option<f32>) -> SensorReading {
+        SensorReading {
+            id,
+            label: format!("sensor {}", id),
+            temperature: Some(temperature),
+            max: None,
+            critical,
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        assert_eq!(reading(0, 50.0, Some(100.0)).level(), TempLevel::Normal);
+        assert_eq!(reading(0, 92.0, Some(100.0)).level(), TempLevel::Hot);
+        assert_eq!(reading(0, 100.0, Some(100.0)).level(), TempLevel::Critical);
+        assert_eq!(reading(0, 85.0, None).level(), TempLevel::Hot);
+        // some drivers report 0 when they have no critical temperature
+        assert_eq!(reading(0, 60.0, Some(0.0)).level(), TempLevel::Normal);
+    }
+
+    #[test]
+    fn test_history_forgets_removed_sensors() {
+        let mut history = SensorHistory::new();
+        history.push(&[reading(0, 40.0, None), reading(1, 30.0, None)]);
+        history.push(&[reading(0, 45.0, None)]);
+
+        assert_eq!(
+            history
+                .get(0)
+                .map(|h| h.iter().copied().collect::<Vec<f32>>()),
+            Some(vec![40.0, 45.0])
+        );
+        assert!(history.get(1).is_none());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let mut history = SensorHistory::new();
+
+        for i in 0..SENSOR_HISTORY_CAPACITY + 5 {
+            history.push(&[reading(0, i as f32, None)]);
+        }
+
+        let cpu = history.get(0).unwrap();
+        assert_eq!(cpu.len(), SENSOR_HISTORY_CAPACITY);
+        assert_eq!(cpu.front(), Some(&5.0));
+    }
+
+    #[test]
+    fn test_history_keeps_duplicate_labels_apart() {
+        let mut history = SensorHistory::new();
+        let mut core0 = reading(0, 40.0, None);
+        let mut other_socket = reading(1, 70.0, None);
+        core0.label = String::from("coretemp Core 0");
+        other_socket.label = String::from("coretemp Core 0");
+
+        history.push(&[core0, other_socket]);
+
+        assert_eq!(history.get(0).map(|h| h.len()), Some(1));
+        assert_eq!(history.get(1).and_then(|h| h.back()), Some(&70.0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::{collections::HashMap, fs, path::Path};
+
+/// size of a `struct utmp` record on glibc for x86_64 and aarch64
+const UTMP_RECORD_SIZE: usize = 384;
+/// `ut_type` of a record for a logged in user
+const USER_PROCESS: i16 = 7;
+const UT_USER_OFFSET: usize = 44;
+const UT_USER_SIZE: usize = 32;
+
+/// counts the logged in sessions of every user according to utmp, `None` when utmp can't be read
+/// (i.e. containers, macos, musl systems without utmp)
+pub fn read_sessions() -> Option<HashMap<String, usize>> {
+    read_sessions_from(Path::new("/var/run/utmp"))
+}
+
+pub fn read_sessions_from(utmp: &Path) -> Option<HashMap<String, usize>> {
+    let content = fs::read(utmp).ok()?;
+
+    let mut ret: HashMap<String, usize> = HashMap::new();
+    for record in content.chunks_exact(UTMP_RECORD_SIZE) {
+        let ut_type = i16::from_ne_bytes([record[0], record[1]]);
+        if ut_type != USER_PROCESS {
+            continue;
+        }
+
+        let user = &record[UT_USER_OFFSET..UT_USER_OFFSET + UT_USER_SIZE];
+        let len = user.iter().position(|b| *b == 0).unwrap_or(UT_USER_SIZE);
+        if len == 0 {
+            continue;
+        }
+
+        *ret.entry(String::from_utf8_lossy(&user[..len]).into_owned())
+            .or_insert(0) += 1;
+    }
+
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+
+    fn record(ut_type: i16, user: &str) -> Vec<u8> {
+        let mut record = vec![0u8; UTMP_RECORD_SIZE];
+        record[0..2].copy_from_slice(&ut_type.to_ne_bytes());
+        record[UT_USER_OFFSET..UT_USER_OFFSET + user.len()].copy_from_slice(user.as_bytes());
+        record
+    }
+
+    #[test]
+    fn test_read_sessions_counts_user_processes() {
+        let path = temp_path("utmp");
+        let content = [
+            // boot time and login records aren't sessions
+            record(2, "reboot"),
+            record(USER_PROCESS, "alice"),
+            record(6, "LOGIN"),
+            record(USER_PROCESS, "alice"),
+            record(USER_PROCESS, "bob"),
+        ]
+        .concat();
+        fs::write(&path, content).unwrap();
+
+        let sessions = read_sessions_from(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["alice"], 2);
+        assert_eq!(sessions["bob"], 1);
+    }
+
+    #[test]
+    fn test_read_sessions_missing_file() {
+        assert!(read_sessions_from(Path::new("/nonexistent/utmp")).is_none());
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use sysinfo::{
+    Components, CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, RefreshKind, System,
+    UpdateKind, Users,
+};
+
+use crate::{
+    cgroup::{cgroup2_root, read_cgroup},
+    cpu::{read_cpus, CpuUsage},
+    cpu_info::{read_cpu_details, CpuDetails},
+    cpu_time::CpuTimes,
+    info::{read_info, read_status, Info, Status},
+    maps::{read_maps, Mapping},
+    namespace::{read_namespaces, read_namespaces_from, read_ns_pids, Namespaces},
+    net::{read_sockets, Socket},
+    power::{read_power, PowerStatus},
+    proc::{read_procs, Proc},
+    proc_mem::{read_proc_memory, ProcMemory},
+    psi::{read_cgroup_pressure, read_pressure, PressureSet},
+    ram::{read_memory, Ram},
+    sensors::{read_sensors, SensorReading},
+    sessions::read_sessions,
+};
+
+/// where `State` gets everything it shows about the machine from
+pub trait DataSource {
+    /// re-reads whatever the data comes from, called once at the start of every refresh
+    fn refresh(&mut self);
+    fn read_procs(&mut self) -> Vec<Proc>;
+    fn read_cpus(&mut self) -> Vec<CpuUsage>;
+    fn read_memory(&mut self) -> Ram;
+    fn read_info(&mut self) -> Info;
+    fn read_status(&mut self) -> Status;
+    fn read_cpu_details(&mut self) -> CpuDetails;
+    fn read_pressure(&mut self) -> PressureSet;
+    fn read_sensors(&mut self) -> Vec<SensorReading>;
+    fn read_power(&mut self) -> PowerStatus;
+    fn read_sockets(&mut self) -> Vec<Socket>;
+    fn read_proc_memory(&mut self, pid: u32) -> Option<ProcMemory>;
+    fn read_maps(&mut self, pid: u32) -> Vec<Mapping>;
+    /// cgroup path of `pid` and the pressure of that group
+    fn read_cgroup_pressure(&mut self, pid: u32) -> Option<(String, PressureSet)>;
+    /// logged in sessions per user
+    fn read_sessions(&mut self) -> Option<HashMap<String, usize>>;
+    /// the namespaces chadtop itself runs in
+    fn read_own_namespaces(&mut self) -> Namespaces;
+    /// the cgroup2 mount the cgroups screen reads and freezing and cpu.max limits write to,
+    /// `None` leaves cgroups alone
+    fn cgroup_root(&self) -> Option<PathBuf>;
+    /// the system processes are signalled, paused and limited through. Processes that aren't in
+    /// it are left alone, which is all of them for sources that don't read this machine
+    fn system(&self) -> &System;
+}
+
+/// the live machine, read with sysinfo
+pub struct SysinfoSource {
+    sys: System,
+    users: Users,
+    components: Components,
+    cpu_times: CpuTimes,
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SysinfoSource {
+    pub fn new() -> SysinfoSource {
+        SysinfoSource {
+            sys: System::new_with_specifics(get_refresh_kind()),
+            users: Users::new_with_refreshed_list(),
+            components: Components::new_with_refreshed_list(),
+            cpu_times: CpuTimes::new(),
+        }
+    }
+}
+
+impl DataSource for SysinfoSource {
+    fn refresh(&mut self) {
+        self.sys.refresh_specifics(get_refresh_kind());
+        self.users.refresh();
+    }
+
+    /// also reads the cgroup and namespaces of every process, which sysinfo doesn't
+    fn read_procs(&mut self) -> Vec<Proc> {
+        let mut processes = read_procs(&mut self.sys, &mut self.users);
+        for p in processes.iter_mut() {
+            p.cgroup = read_cgroup(p.pid);
+            p.namespaces = read_namespaces(p.pid);
+            p.ns_pids = read_ns_pids(p.pid);
+        }
+        processes
+    }
+
+    /// with the /proc/stat breakdown of every cpu since the last call
+    fn read_cpus(&mut self) -> Vec<CpuUsage> {
+        let mut cpus = read_cpus(&mut self.sys);
+        self.cpu_times.sample();
+        self.cpu_times.apply(&mut cpus);
+        cpus
+    }
+
+    fn read_memory(&mut self) -> Ram {
+        read_memory(&mut self.sys)
+    }
+
+    fn read_info(&mut self) -> Info {
+        read_info()
+    }
+
+    fn read_status(&mut self) -> Status {
+        read_status(&self.sys)
+    }
+
+    fn read_cpu_details(&mut self) -> CpuDetails {
+        read_cpu_details(&self.sys)
+    }
+
+    fn read_pressure(&mut self) -> PressureSet {
+        read_pressure()
+    }
+
+    fn read_sensors(&mut self) -> Vec<SensorReading> {
+        self.components.refresh(false);
+        read_sensors(&self.components)
+    }
+
+    fn read_power(&mut self) -> PowerStatus {
+        read_power()
+    }
+
+    fn read_sockets(&mut self) -> Vec<Socket> {
+        read_sockets()
+    }
+
+    fn read_proc_memory(&mut self, pid: u32) -> Option<ProcMemory> {
+        read_proc_memory(pid)
+    }
+
+    fn read_maps(&mut self, pid: u32) -> Vec<Mapping> {
+        read_maps(pid)
+    }
+
+    fn read_cgroup_pressure(&mut self, pid: u32) -> Option<(String, PressureSet)> {
+        read_cgroup_pressure(pid)
+    }
+
+    fn read_sessions(&mut self) -> Option<HashMap<String, usize>> {
+        read_sessions()
+    }
+
+    fn read_own_namespaces(&mut self) -> Namespaces {
+        read_namespaces_from(Path::new("/proc/self"))
+    }
+
+    fn cgroup_root(&self) -> Option<PathBuf> {
+        cgroup2_root()
+    }
+
+    fn system(&self) -> &System {
+        &self.sys
+    }
+}
+
+fn get_refresh_kind() -> RefreshKind {
+    RefreshKind::nothing()
+        .with_memory(MemoryRefreshKind::everything())
+        .with_cpu(CpuRefreshKind::nothing().with_cpu_usage().with_frequency())
+        .with_processes(
+            ProcessRefreshKind::nothing()
+                .with_cmd(UpdateKind::Always)
+                .with_exe(UpdateKind::Always)
+                .with_cwd(UpdateKind::Always)
+                .with_memory()
+                .with_cpu()
+                .with_disk_usage()
+                .with_user(UpdateKind::Always)
+                .without_tasks(),
+        )
+}
+
+/// the same made up system on every refresh, for tests that shouldn't depend on the machine
+/// they run on. Its `System` is empty and it has no cgroup root unless a test points it at one,
+/// so nothing it lists can be killed, paused or limited. Nothing is read from /proc or /sys
+pub struct FixtureSource {
+    pub processes: Vec<Proc>,
+    pub cpus: Vec<CpuUsage>,
+    pub ram: Ram,
+    pub info: Info,
+    pub status: Status,
+    pub pressure: PressureSet,
+    pub sockets: Vec<Socket>,
+    /// returned for whichever pid is asked for
+    pub maps: Vec<Mapping>,
+    pub sessions: Option<HashMap<String, usize>>,
+    pub cgroup_root: Option<PathBuf>,
+    sys: System,
+}
+
+impl FixtureSource {
+    /// `processes` on a 4 cpu box with 16GiB of memory
+    pub fn new(processes: Vec<Proc>) -> FixtureSource {
+        let cpu = |name: &str, usage: f32| CpuUsage {
+            name: name.to_string(),
+            usage,
+            frequency: None,
+            breakdown: None,
+        };
+        let gib = 1024 * 1024 * 1024;
+
+        FixtureSource {
+            processes,
+            cpus: vec![
+                cpu("overall", 25.0),
+                cpu("cpu0", 10.0),
+                cpu("cpu1", 20.0),
+                cpu("cpu2", 30.0),
+                cpu("cpu3", 40.0),
+            ],
+            ram: Ram {
+                total: 16 * gib,
+                free: 4 * gib,
+                available: 8 * gib,
+                used: 8 * gib,
+                total_swap: 2 * gib,
+                free_swap: 2 * gib,
+                used_swap: 0,
+            },
+            info: Info {
+                long_os_version: Some(String::from("Linux (fixture)")),
+                kernel_long_version: String::from("Linux 6.0.0-fixture"),
+                host_name: Some(String::from("fixture")),
+                cpu_arch: String::from("x86_64"),
+                physical_core_count: Some(4),
+            },
+            status: Status {
+                load_one: 0.5,
+                load_five: 0.25,
+                load_fifteen: 0.1,
+                uptime: 3600,
+                boot_time: 1_700_000_000,
+                process_count: 0,
+                thread_count: None,
+            },
+            pressure: PressureSet::default(),
+            sockets: Vec::new(),
+            maps: Vec::new(),
+            sessions: None,
+            cgroup_root: None,
+            sys: System::new(),
+        }
+    }
+}
+
+impl DataSource for FixtureSource {
+    fn refresh(&mut self) {}
+
+    fn read_procs(&mut self) -> Vec<Proc> {
+        self.processes.clone()
+    }
+
+    fn read_cpus(&mut self) -> Vec<CpuUsage> {
+        self.cpus.clone()
+    }
+
+    fn read_memory(&mut self) -> Ram {
+        self.ram
+    }
+
+    fn read_info(&mut self) -> Info {
+        self.info.clone()
+    }
+
+    /// `status` with the process count of `processes`
+    fn read_status(&mut self) -> Status {
+        Status {
+            process_count: self.processes.len(),
+            ..self.status.clone()
+        }
+    }
+
+    fn read_cpu_details(&mut self) -> CpuDetails {
+        CpuDetails::default()
+    }
+
+    fn read_pressure(&mut self) -> PressureSet {
+        self.pressure
+    }
+
+    fn read_sensors(&mut self) -> Vec<SensorReading> {
+        Vec::new()
+    }
+
+    fn read_power(&mut self) -> PowerStatus {
+        PowerStatus::default()
+    }
+
+    fn read_sockets(&mut self) -> Vec<Socket> {
+        self.sockets.clone()
+    }
+
+    /// the `memory_detail` the process was given
+    fn read_proc_memory(&mut self, pid: u32) -> Option<ProcMemory> {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .and_then(|p| p.memory_detail.clone())
+    }
+
+    fn read_maps(&mut self, _pid: u32) -> Vec<Mapping> {
+        self.maps.clone()
+    }
+
+    fn read_cgroup_pressure(&mut self, _pid: u32) -> Option<(String, PressureSet)> {
+        None
+    }
+
+    fn read_sessions(&mut self) -> Option<HashMap<String, usize>> {
+        self.sessions.clone()
+    }
+
+    fn read_own_namespaces(&mut self) -> Namespaces {
+        Namespaces::default()
+    }
+
+    fn cgroup_root(&self) -> Option<PathBuf> {
+        self.cgroup_root.clone()
+    }
+
+    fn system(&self) -> &System {
+        &self.sys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_source_returns_its_data() {
+        let mut source = FixtureSource::new(vec![Proc {
+            pid: 42,
+            ..Default::default()
+        }]);
+
+        source.refresh();
+
+        assert_eq!(source.read_procs()[0].pid, 42);
+        assert_eq!(source.read_procs().len(), 1);
+        assert_eq!(source.read_cpus()[0].name, "overall");
+        assert_eq!(source.read_memory().total, 16 * 1024 * 1024 * 1024);
+        assert_eq!(source.read_info().host_name.as_deref(), Some("fixture"));
+        assert_eq!(source.read_status().process_count, 1);
+        assert!(source.read_sensors().is_empty());
+        assert_eq!(source.cgroup_root(), None);
+        assert!(source.system().processes().is_empty());
+    }
+}
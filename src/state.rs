@@ -1,18 +1,38 @@
 use std::{
     cmp::Reverse,
+    collections::{HashMap, HashSet},
     fmt::{self, Display},
     time::{Duration, Instant},
 };
-use sysinfo::{
-    CpuRefreshKind, MemoryRefreshKind, Pid, ProcessRefreshKind, RefreshKind, System, UpdateKind,
-    Users,
-};
+use sysinfo::Pid;
 
 use crate::{
-    cpu::{read_cpus, CpuUsage},
-    info::{read_info, Info},
-    proc::{read_procs, Proc},
-    ram::{read_memory, Ram},
+    alerts::AlertEngine,
+    audit::{current_user, AuditEntry, AuditLog},
+    cgroup::read_cgroup,
+    cgroup_tree::CgroupTree,
+    clock::unix_now,
+    cpu::CpuUsage,
+    cpu_info::CpuDetails,
+    crashloop::{detect_crash_loops, CrashLoop, CrashLoopRule},
+    events::EventLog,
+    filter::{parse_filter, FilterContext, FilterTerm},
+    group::{group_procs, GroupBy, ProcGroup},
+    info::{Info, Status},
+    maps::{group_by_file, Mapping},
+    namespace::{Namespaces, NsKind},
+    net::{NetThroughput, Socket},
+    pause::{PauseTracker, Resumed},
+    power::PowerStatus,
+    problems::{Problem, ProblemTracker},
+    proc::Proc,
+    proc_mem::MemoryMetric,
+    psi::{PressureSet, PsiHistory},
+    ram::Ram,
+    record::{Recorder, Replay},
+    sensors::{SensorHistory, SensorReading},
+    source::{DataSource, SysinfoSource},
+    throttle::Throttler,
 };
 use ratatui::{
     crossterm::event::{KeyCode, KeyEvent},
@@ -28,6 +48,43 @@ pub enum CurrentScreen {
     SysInfo,
     Help,
     KillConfirm,
+    Ports,
+    Problems,
+    Events,
+    CrashLoops,
+    Users,
+    Sensors,
+    Cgroups,
+    ResumeConfirm,
+    Limit,
+    Alerts,
+    Audit,
+}
+
+/// the process or group pause and limit act on
+struct SelectedTarget {
+    /// names the limit, i.e. `pid 42` or `name firefox`
+    label: String,
+    /// (pid, start time) of every member
+    pids: Vec<(u32, u64)>,
+    /// the cgroup every member of a cgroup group shares, `None` for everything else
+    shared_cgroup: Option<String>,
+}
+
+/// the tabs of the process info popup
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProcInfoTab {
+    Overview,
+    Maps,
+}
+
+/// a row of the process table, either a group of processes or a single process
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProcRow {
+    /// index into `State::groups`
+    Group(usize),
+    /// index into `State::processes`
+    Proc(usize),
 }
 
 pub enum ProcessSortStrategy {
@@ -37,6 +94,8 @@ pub enum ProcessSortStrategy {
     CpuUsage,
     Alphabetical,
     Memory,
+    NetRx,
+    NetTx,
 }
 
 impl Display for ProcessSortStrategy {
@@ -48,6 +107,8 @@ impl Display for ProcessSortStrategy {
             ProcessSortStrategy::CpuUsage => "cpu usage",
             ProcessSortStrategy::Alphabetical => "name",
             ProcessSortStrategy::Memory => "memory",
+            ProcessSortStrategy::NetRx => "net rx",
+            ProcessSortStrategy::NetTx => "net tx",
         };
 
         write!(f, "{}", text)
@@ -63,22 +124,104 @@ impl ProcessSortStrategy {
             ProcessSortStrategy::CpuUsage => Color::Green,
             ProcessSortStrategy::Alphabetical => Color::LightRed,
             ProcessSortStrategy::Memory => Color::LightMagenta,
+            ProcessSortStrategy::NetRx => Color::LightGreen,
+            ProcessSortStrategy::NetTx => Color::LightYellow,
         }
     }
 }
 
 pub struct State {
     pub exit: bool,
-    pub sys: System,
-    pub users: Users,
+    /// where processes, cpus, memory and info come from, the live system outside of tests
+    pub source: Box<dyn DataSource>,
     pub processes: Vec<Proc>,
+    pub groups: Vec<ProcGroup>,
+    /// what `processes_state` indexes into, built from `processes` and `groups`
+    pub rows: Vec<ProcRow>,
+    pub group_by: GroupBy,
+    /// keys of the groups showing their members
+    pub expanded_groups: HashSet<String>,
     pub cpus: Vec<CpuUsage>,
+    pub pressure: PressureSet,
+    pub psi_history: PsiHistory,
+    /// cgroup path and pressure of the process in the process info popup
+    pub cgroup_pressure: Option<(String, PressureSet)>,
+    pub sensors: Vec<SensorReading>,
+    pub sensor_history: SensorHistory,
+    pub power: PowerStatus,
+    /// every cgroup v2 group, only read while the cgroups screen is open
+    pub cgroup_tree: CgroupTree,
     pub ram: Ram,
     pub info: Info,
+    pub cpu_details: CpuDetails,
+    /// the namespaces chadtop itself runs in, to tell which processes are isolated from it
+    pub own_namespaces: Namespaces,
+    pub status: Status,
+    pub sockets: Vec<Socket>,
+    pub maps: Vec<Mapping>,
+    pub problems: Vec<Problem>,
+    pub problem_tracker: ProblemTracker,
+    pub event_log: EventLog,
+    pub crash_loops: Vec<CrashLoop>,
+    pub crash_loop_rule: CrashLoopRule,
+    /// rules from the alerts config, evaluated every refresh. Empty until main loads the config
+    pub alerts: AlertEngine,
+    /// everything chadtop did to other processes, only kept in memory until main opens the file
+    pub audit_log: AuditLog,
+    /// the user running chadtop, recorded in the audit log
+    pub actor: String,
+    /// `--record`, every refresh is appended to it
+    pub recorder: Option<Recorder>,
+    /// why recording stopped
+    pub record_error: Option<String>,
+    /// set by `refresh` so only refreshes are recorded and not every key press
+    record_due: bool,
+    /// every process as of the last refresh or replay frame. Reading them walks /proc a few
+    /// times per process so keys filter and sort a copy of these instead of reading again
+    all_processes: Vec<Proc>,
+    /// `--replay`, processes, cpus, ram and info come from the recording instead of `sys`
+    pub replay: Option<Replay>,
+    /// every process on the system grouped by user, only kept up to date on the users screen
+    pub user_summaries: Vec<ProcGroup>,
+    /// logged in sessions per user, `None` when utmp isn't available
+    pub sessions: Option<HashMap<String, usize>>,
+    /// how long a process has to be in uninterruptible sleep before it shows up in problems
+    pub disk_sleep_threshold: Duration,
+    pub net_throughput: NetThroughput,
+    /// processes and cgroups paused from chadtop, offered to be resumed on quit
+    pub pause_tracker: PauseTracker,
+    /// cpu limits, dropping it removes them so it has to live as long as chadtop
+    pub throttler: Throttler,
+    /// what's typed in the limit popup
+    pub limit_input: String,
+    /// label of the process or group the limit popup is for
+    pub limit_label: Option<String>,
     pub processes_state: TableState,
+    pub sockets_state: TableState,
+    pub maps_state: TableState,
+    pub problems_state: TableState,
+    /// rows are shown newest first so index 0 is the last event in `event_log`
+    pub events_state: TableState,
+    pub crash_loops_state: TableState,
+    pub user_summaries_state: TableState,
+    pub sensors_state: TableState,
+    pub cgroup_tree_state: TableState,
+    /// rows are shown newest first so index 0 is the last event in `alerts.history`
+    pub alert_history_state: TableState,
+    /// rows are shown newest first so index 0 is the last entry in `audit_log`
+    pub audit_state: TableState,
+    pub proc_info_tab: ProcInfoTab,
+    /// whether the maps tab shows mappings grouped by file or every mapping on its own
+    pub maps_grouped: bool,
     pub process_sort_strategy: ProcessSortStrategy,
+    pub memory_metric: MemoryMetric,
     pub current_screen: CurrentScreen,
     pub current_pid_watch: Option<u32>,
+    /// key of the group the kill confirm popup is for, takes priority over `current_pid_watch`
+    pub current_group_watch: Option<String>,
+    /// (pid, start time) of every process the kill confirm popup was opened for, only these are
+    /// killed even if the group gained members or a pid was reused since
+    pub kill_targets: Vec<(u32, u64)>,
     pub filter: String,
     pub debug: bool,
     pub debug_stats_sys: Option<Duration>,
@@ -89,45 +232,133 @@ pub struct State {
 
 impl State {
     pub fn new(debug: bool) -> State {
+        State::with_source(debug, Box::new(SysinfoSource::new()))
+    }
+
+    pub fn with_source(debug: bool, source: Box<dyn DataSource>) -> State {
         let mut new = State {
             exit: false,
-            sys: System::new_with_specifics(get_refresh_kind()),
-            users: Users::new_with_refreshed_list(),
+            source,
             processes: Vec::new(),
+            groups: Vec::new(),
+            rows: Vec::new(),
+            group_by: GroupBy::None,
+            expanded_groups: HashSet::new(),
             cpus: Vec::new(),
+            pressure: PressureSet::default(),
+            psi_history: PsiHistory::new(),
+            cgroup_pressure: None,
+            sensors: Vec::new(),
+            sensor_history: SensorHistory::new(),
+            power: PowerStatus::default(),
+            cgroup_tree: CgroupTree::new(),
             ram: Ram::new(),
-            info: read_info(),
+            info: Info::default(),
+            cpu_details: CpuDetails::default(),
+            own_namespaces: Namespaces::default(),
+            status: Status::default(),
+            sockets: Vec::new(),
+            maps: Vec::new(),
+            problems: Vec::new(),
+            problem_tracker: ProblemTracker::new(),
+            event_log: EventLog::new(),
+            crash_loops: Vec::new(),
+            crash_loop_rule: CrashLoopRule::default(),
+            alerts: AlertEngine::default(),
+            audit_log: AuditLog::default(),
+            actor: String::new(),
+            recorder: None,
+            record_error: None,
+            record_due: false,
+            all_processes: Vec::new(),
+            replay: None,
+            user_summaries: Vec::new(),
+            sessions: None,
+            disk_sleep_threshold: Duration::from_secs(10),
+            net_throughput: NetThroughput::new(),
+            pause_tracker: PauseTracker::new(),
+            throttler: Throttler::new(),
+            limit_input: String::new(),
+            limit_label: None,
             processes_state: TableState::default(),
+            sockets_state: TableState::default(),
+            maps_state: TableState::default(),
+            problems_state: TableState::default(),
+            events_state: TableState::default(),
+            crash_loops_state: TableState::default(),
+            user_summaries_state: TableState::default(),
+            sensors_state: TableState::default(),
+            cgroup_tree_state: TableState::default(),
+            alert_history_state: TableState::default(),
+            audit_state: TableState::default(),
+            proc_info_tab: ProcInfoTab::Overview,
+            maps_grouped: true,
             process_sort_strategy: ProcessSortStrategy::CpuUsage,
+            memory_metric: MemoryMetric::Rss,
             current_screen: CurrentScreen::Main,
             current_pid_watch: None,
+            current_group_watch: None,
+            kill_targets: Vec::new(),
             filter: String::new(),
-            debug: debug,
+            debug,
             debug_stats_sys: None,
             debug_stats_state: None,
             debug_stats_ram: None,
             debug_stats_cpu: None,
         };
+        new.info = new.source.read_info();
+        new.cpu_details = new.source.read_cpu_details();
+        new.own_namespaces = new.source.read_own_namespaces();
+        new.actor = current_user();
         new.refresh();
         new
     }
 
     pub fn handle_key(&mut self, key: &KeyEvent) {
+        if self.replay.is_some() && self.is_live_action(key.code) {
+            return;
+        }
+        if matches!(self.current_screen, CurrentScreen::Main) && self.handle_replay_key(key.code) {
+            return;
+        }
+
         match self.current_screen {
             CurrentScreen::Main => match key.code {
-                KeyCode::Char('q') => self.exit = true,
+                KeyCode::Char('q') => self.quit(),
                 KeyCode::Char('j') => self.next_row(),
                 KeyCode::Char('k') => self.previous_row(),
                 KeyCode::Char('g') => self.first(),
                 KeyCode::Char('G') => self.last(),
                 KeyCode::Char('s') => self.next_sort_strategy(),
+                KeyCode::Char('m') => {
+                    self.memory_metric = self.memory_metric.next();
+                    self.refresh_procs();
+                }
                 KeyCode::Esc => self.select_none(),
+                KeyCode::Char('a') => {
+                    self.group_by = self.group_by.next();
+                    self.processes_state.select(None);
+                    self.refresh_procs();
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    if let Some(ProcRow::Group(idx)) = self.selected_row() {
+                        let key = self.groups[idx].key.clone();
+                        if !self.expanded_groups.remove(&key) {
+                            self.expanded_groups.insert(key);
+                        }
+                        self.build_rows();
+                    }
+                }
                 KeyCode::Char('d') => {
-                    match self.processes_state.selected() {
-                        Some(idx) => {
+                    match self.selected_row() {
+                        Some(ProcRow::Proc(idx)) => {
                             self.current_pid_watch = Some(self.processes[idx].pid);
                             self.current_screen = CurrentScreen::ProcInfo;
+                            self.proc_info_tab = ProcInfoTab::Overview;
+                            self.refresh_procs();
                         }
+                        // groups don't have a process info popup
+                        Some(ProcRow::Group(_)) => {}
                         None => {
                             self.current_pid_watch = None;
                             self.current_screen = CurrentScreen::Main;
@@ -135,30 +366,90 @@ impl State {
                     };
                 }
                 KeyCode::Char('K') => {
-                    match self.processes_state.selected() {
-                        Some(idx) => {
-                            self.current_pid_watch = Some(self.processes[idx].pid);
+                    match self.selected_row() {
+                        Some(ProcRow::Proc(idx)) => {
+                            let proc = &self.processes[idx];
+                            self.current_pid_watch = Some(proc.pid);
+                            self.current_group_watch = None;
+                            self.kill_targets = vec![(proc.pid, proc.start_time)];
+                            self.current_screen = CurrentScreen::KillConfirm;
+                        }
+                        Some(ProcRow::Group(idx)) => {
+                            let group = &self.groups[idx];
+                            self.current_pid_watch = None;
+                            self.current_group_watch = Some(group.key.clone());
+                            self.kill_targets = group
+                                .members
+                                .iter()
+                                .map(|m| (self.processes[*m].pid, self.processes[*m].start_time))
+                                .collect();
                             self.current_screen = CurrentScreen::KillConfirm;
                         }
                         None => {
                             self.current_pid_watch = None;
+                            self.current_group_watch = None;
+                            self.kill_targets.clear();
                             self.current_screen = CurrentScreen::Main;
                         }
                     };
                 }
+                KeyCode::Char('P') => self.pause_selected(true),
+                KeyCode::Char('R') => self.pause_selected(false),
+                KeyCode::Char('L') => self.open_limit(),
                 KeyCode::Char('i') => self.current_screen = CurrentScreen::SysInfo,
                 KeyCode::Char('h') => self.current_screen = CurrentScreen::Help,
                 KeyCode::Char('f') => self.current_screen = CurrentScreen::Filter,
+                KeyCode::Char('p') => {
+                    self.current_screen = CurrentScreen::Ports;
+                    self.refresh_sockets();
+                }
+                KeyCode::Char('z') => self.current_screen = CurrentScreen::Problems,
+                KeyCode::Char('e') => self.current_screen = CurrentScreen::Events,
+                KeyCode::Char('l') => self.current_screen = CurrentScreen::CrashLoops,
+                KeyCode::Char('t') => self.current_screen = CurrentScreen::Sensors,
+                KeyCode::Char('n') => self.filter_same_namespace(),
+                KeyCode::Char('!') => self.current_screen = CurrentScreen::Alerts,
+                KeyCode::Char('H') => self.current_screen = CurrentScreen::Audit,
+                KeyCode::Char('c') => {
+                    self.current_screen = CurrentScreen::Cgroups;
+                    self.refresh_cgroup_tree();
+                }
+                KeyCode::Char('u') => {
+                    self.current_screen = CurrentScreen::Users;
+                    self.refresh_procs();
+                }
                 KeyCode::Backspace => {
                     self.filter.clear();
                     self.refresh_procs();
                 }
                 _ => {}
             },
-            CurrentScreen::ProcInfo => match key.code {
-                KeyCode::Char('d') => self.current_screen = CurrentScreen::Main,
-                _ => {}
-            },
+            CurrentScreen::ProcInfo => {
+                let maps_rows = self.maps_rows();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('d') => {
+                        self.current_screen = CurrentScreen::Main;
+                        self.maps.clear();
+                    }
+                    KeyCode::Tab => {
+                        self.proc_info_tab = match self.proc_info_tab {
+                            ProcInfoTab::Overview => ProcInfoTab::Maps,
+                            ProcInfoTab::Maps => ProcInfoTab::Overview,
+                        };
+                        self.maps_state.select(None);
+                        self.refresh_maps();
+                    }
+                    KeyCode::Char('v') => {
+                        self.maps_grouped = !self.maps_grouped;
+                        self.maps_state.select(None);
+                    }
+                    KeyCode::Char('j') => next_row_in(&mut self.maps_state, maps_rows),
+                    KeyCode::Char('k') => previous_row_in(&mut self.maps_state, maps_rows),
+                    KeyCode::Char('g') => first_in(&mut self.maps_state, maps_rows),
+                    KeyCode::Char('G') => last_in(&mut self.maps_state, maps_rows),
+                    _ => {}
+                }
+            }
             CurrentScreen::Filter => match key.code {
                 KeyCode::Esc | KeyCode::Enter => self.current_screen = CurrentScreen::Main,
                 KeyCode::Char(value) => {
@@ -181,22 +472,318 @@ impl State {
             },
             CurrentScreen::KillConfirm => match key.code {
                 KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
+                    self.kill_targets.clear();
                     self.current_screen = CurrentScreen::Main
                 }
-                KeyCode::Char('y') | KeyCode::Char('Y') => match self.current_pid_watch {
-                    Some(pid) => {
-                        self.sys
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    let now = unix_now();
+                    for (pid, start_time) in std::mem::take(&mut self.kill_targets) {
+                        // a different start time means the pid now belongs to another process
+                        let sent = self
+                            .source
+                            .system()
                             .process(Pid::from_u32(pid))
-                            .and_then(|p| Some(p.kill()));
+                            .filter(|p| p.start_time() == start_time)
+                            .map(|p| p.kill());
+                        // exited processes are still logged, there's just less to say about them
+                        let exited = Proc {
+                            pid,
+                            start_time,
+                            ..Default::default()
+                        };
+                        let target = self
+                            .processes
+                            .iter()
+                            .find(|p| p.pid == pid && p.start_time == start_time)
+                            .unwrap_or(&exited);
+                        let mut entry = AuditEntry::new(
+                            now,
+                            &self.actor,
+                            "kill",
+                            target,
+                            String::from("signal KILL"),
+                        );
+                        entry.result = match sent {
+                            Some(true) => String::from("ok"),
+                            Some(false) => String::from("failed"),
+                            None => String::from("failed: process exited"),
+                        };
+                        self.audit_log.record(entry);
+                    }
+
+                    self.refresh_procs();
+                    self.current_pid_watch = None;
+                    self.current_group_watch = None;
+                    self.current_screen = CurrentScreen::Main;
+                }
+                _ => {}
+            },
+            CurrentScreen::Limit => match key.code {
+                KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+                KeyCode::Enter => {
+                    self.apply_limit();
+                    self.limit_label = None;
+                    self.current_screen = CurrentScreen::Main;
+                }
+                KeyCode::Char(c) if c.is_ascii_digit() || c == '.' => self.limit_input.push(c),
+                KeyCode::Backspace => {
+                    self.limit_input.pop();
+                }
+                _ => {}
+            },
+            CurrentScreen::ResumeConfirm => match key.code {
+                KeyCode::Esc => self.current_screen = CurrentScreen::Main,
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    let results = self
+                        .pause_tracker
+                        .resume_all(self.source.system(), self.source.cgroup_root().as_deref());
+                    for (resumed, ok) in results {
+                        match resumed {
+                            Resumed::Process(pid) => {
+                                let target = self.audit_target(pid);
+                                self.record_action(
+                                    "resume",
+                                    &target,
+                                    "signal CONT",
+                                    ok_or_failed(ok),
+                                );
+                            }
+                            Resumed::Cgroup(path) => self.record_action(
+                                "thaw",
+                                &cgroup_target(&path),
+                                "cgroup.freeze 0",
+                                ok_or_failed(ok),
+                            ),
+                        }
+                    }
+                    self.exit = true;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') => self.exit = true,
+                _ => {}
+            },
+            CurrentScreen::Ports => match key.code {
+                KeyCode::Esc | KeyCode::Char('p') => self.current_screen = CurrentScreen::Main,
+                KeyCode::Char('j') => next_row_in(&mut self.sockets_state, self.sockets.len()),
+                KeyCode::Char('k') => previous_row_in(&mut self.sockets_state, self.sockets.len()),
+                KeyCode::Char('g') => first_in(&mut self.sockets_state, self.sockets.len()),
+                KeyCode::Char('G') => last_in(&mut self.sockets_state, self.sockets.len()),
+                KeyCode::Enter => {
+                    let pid = self
+                        .sockets_state
+                        .selected()
+                        .and_then(|idx| self.sockets.get(idx))
+                        .and_then(|s| s.pid);
+
+                    if let Some(pid) = pid {
+                        self.select_pid(pid);
+                        self.current_screen = CurrentScreen::Main;
+                    }
+                }
+                _ => {}
+            },
+            CurrentScreen::Events => {
+                let len = self.event_log.events.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('e') => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Char('j') => next_row_in(&mut self.events_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.events_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.events_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.events_state, len),
+                    KeyCode::Enter => {
+                        let pid = self
+                            .events_state
+                            .selected()
+                            .and_then(|idx| self.event_log.events.iter().rev().nth(idx))
+                            .map(|e| e.pid);
+
+                        // exited processes can't be jumped to
+                        if let Some(pid) = pid {
+                            if self.source.system().process(Pid::from_u32(pid)).is_some() {
+                                self.select_pid(pid);
+                                self.current_screen = CurrentScreen::Main;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            CurrentScreen::CrashLoops => {
+                let len = self.crash_loops.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('l') => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Char('j') => next_row_in(&mut self.crash_loops_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.crash_loops_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.crash_loops_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.crash_loops_state, len),
+                    KeyCode::Char('+') => self.set_crash_loop_rule(|r| r.set_max_exits(1)),
+                    KeyCode::Char('-') => self.set_crash_loop_rule(|r| r.set_max_exits(-1)),
+                    KeyCode::Char('>') => self.set_crash_loop_rule(|r| r.set_window(10)),
+                    KeyCode::Char('<') => self.set_crash_loop_rule(|r| r.set_window(-10)),
+                    KeyCode::Enter => {
+                        // the looping process has exited, filter by name to find the instance
+                        // that is currently running (if any)
+                        let name = self
+                            .crash_loops_state
+                            .selected()
+                            .and_then(|idx| self.crash_loops.get(idx))
+                            .and_then(|l| l.name.clone());
+
+                        if let Some(name) = name {
+                            self.filter = name;
+                            self.refresh_procs();
+                            self.current_screen = CurrentScreen::Main;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            CurrentScreen::Sensors => {
+                let len = self.sensors.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('t') => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Char('j') => next_row_in(&mut self.sensors_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.sensors_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.sensors_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.sensors_state, len),
+                    _ => {}
+                }
+            }
+            CurrentScreen::Cgroups => {
+                let len = self.cgroup_tree.nodes.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('c') => {
+                        self.current_screen = CurrentScreen::Main;
+                        self.cgroup_tree.clear();
+                    }
+                    KeyCode::Char('j') => next_row_in(&mut self.cgroup_tree_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.cgroup_tree_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.cgroup_tree_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.cgroup_tree_state, len),
+                    KeyCode::Char('P') | KeyCode::Char('R') => {
+                        let path = self
+                            .cgroup_tree_state
+                            .selected()
+                            .and_then(|idx| self.cgroup_tree.nodes.get(idx))
+                            .map(|node| node.path.clone());
+
+                        if let Some(path) = path {
+                            self.freeze_cgroup(&path, key.code == KeyCode::Char('P'));
+                            self.refresh_cgroup_tree();
+                        }
+                    }
+                    KeyCode::Enter => {
+                        let path = self
+                            .cgroup_tree_state
+                            .selected()
+                            .and_then(|idx| self.cgroup_tree.nodes.get(idx))
+                            .map(|node| node.path.clone());
+
+                        if let Some(path) = path {
+                            self.filter = format!("cgroup:{}", path);
+                            self.current_screen = CurrentScreen::Main;
+                            self.cgroup_tree.clear();
+                            self.processes_state.select(None);
+                            self.refresh_procs();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            CurrentScreen::Alerts => {
+                let len = self.alerts.history.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('!') => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Char('j') => next_row_in(&mut self.alert_history_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.alert_history_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.alert_history_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.alert_history_state, len),
+                    KeyCode::Enter => {
+                        let pid = self
+                            .alert_history_state
+                            .selected()
+                            .and_then(|idx| self.alerts.history.iter().rev().nth(idx))
+                            .and_then(|e| e.pid);
 
-                        self.refresh_procs();
-                        self.current_pid_watch = None;
+                        if let Some(pid) = pid {
+                            if self.source.system().process(Pid::from_u32(pid)).is_some() {
+                                self.select_pid(pid);
+                                self.current_screen = CurrentScreen::Main;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            CurrentScreen::Audit => {
+                let len = self.audit_log.entries.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('H') => self.current_screen = CurrentScreen::Main,
+                    KeyCode::Char('j') => next_row_in(&mut self.audit_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.audit_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.audit_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.audit_state, len),
+                    _ => {}
+                }
+            }
+            CurrentScreen::Users => {
+                let len = self.user_summaries.len();
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('u') => {
                         self.current_screen = CurrentScreen::Main;
+                        self.user_summaries.clear();
+                    }
+                    KeyCode::Char('j') => next_row_in(&mut self.user_summaries_state, len),
+                    KeyCode::Char('k') => previous_row_in(&mut self.user_summaries_state, len),
+                    KeyCode::Char('g') => first_in(&mut self.user_summaries_state, len),
+                    KeyCode::Char('G') => last_in(&mut self.user_summaries_state, len),
+                    KeyCode::Enter => {
+                        let user = self
+                            .user_summaries_state
+                            .selected()
+                            .and_then(|idx| self.user_summaries.get(idx))
+                            .map(|summary| summary.key.clone());
+
+                        if let Some(user) = user {
+                            self.filter = format!("user:{}", user);
+                            self.current_screen = CurrentScreen::Main;
+                            self.user_summaries.clear();
+                            self.processes_state.select(None);
+                            self.refresh_procs();
+                        }
                     }
-                    None => {
+                    _ => {}
+                }
+            }
+            CurrentScreen::Problems => match key.code {
+                KeyCode::Esc | KeyCode::Char('z') => self.current_screen = CurrentScreen::Main,
+                KeyCode::Char('j') => next_row_in(&mut self.problems_state, self.problems.len()),
+                KeyCode::Char('k') => {
+                    previous_row_in(&mut self.problems_state, self.problems.len())
+                }
+                KeyCode::Char('g') => first_in(&mut self.problems_state, self.problems.len()),
+                KeyCode::Char('G') => last_in(&mut self.problems_state, self.problems.len()),
+                KeyCode::Char('+') => {
+                    self.disk_sleep_threshold += Duration::from_secs(5);
+                    self.refresh_procs();
+                }
+                KeyCode::Char('-') => {
+                    self.disk_sleep_threshold = self
+                        .disk_sleep_threshold
+                        .saturating_sub(Duration::from_secs(5));
+                    self.refresh_procs();
+                }
+                KeyCode::Enter => {
+                    let pid = self
+                        .problems_state
+                        .selected()
+                        .and_then(|idx| self.problems.get(idx))
+                        .map(|p| p.actionable_pid());
+
+                    if let Some(pid) = pid {
+                        self.select_pid(pid);
                         self.current_screen = CurrentScreen::Main;
                     }
-                },
+                }
                 _ => {}
             },
         }
@@ -213,50 +800,305 @@ impl State {
             ProcessSortStrategy::Alphabetical => {
                 self.process_sort_strategy = ProcessSortStrategy::Memory
             }
-            ProcessSortStrategy::Memory => self.process_sort_strategy = ProcessSortStrategy::User,
+            ProcessSortStrategy::Memory => self.process_sort_strategy = ProcessSortStrategy::NetRx,
+            ProcessSortStrategy::NetRx => self.process_sort_strategy = ProcessSortStrategy::NetTx,
+            ProcessSortStrategy::NetTx => self.process_sort_strategy = ProcessSortStrategy::User,
         };
         self.refresh_procs();
     }
 
     pub fn refresh(&mut self) {
-        self.sys.refresh_specifics(get_refresh_kind());
-        self.users.refresh();
+        if self.replay.is_some() {
+            return self.refresh_replay();
+        }
+
+        self.source.refresh();
+        self.net_throughput
+            .sample(self.source.system().processes().keys().map(|p| p.as_u32()));
+        self.ram = self.source.read_memory();
+        self.cpus = self.source.read_cpus();
+        self.pressure = self.source.read_pressure();
+        self.psi_history.push(&self.pressure);
+        self.sensors = self.source.read_sensors();
+        self.sensor_history.push(&self.sensors);
+        self.power = self.source.read_power();
+        self.status = self.source.read_status();
+        self.refresh_sockets();
+        self.read_procs();
+        // after ram and cpus so the recorded frame and alerts see this refresh's values
+        self.record_due = true;
         self.refresh_procs();
-        self.ram = read_memory(&mut self.sys);
-        self.cpus = read_cpus(&mut self.sys);
+        self.refresh_maps();
+        self.refresh_cgroup_tree();
     }
 
     /// for use in debug mode. sets members of state with debug information
     pub fn debug_refresh(&mut self) {
+        if self.replay.is_some() {
+            return self.refresh_replay();
+        }
+
         let i = Instant::now();
 
-        self.sys.refresh_specifics(get_refresh_kind());
-        self.users.refresh();
+        self.source.refresh();
+        self.net_throughput
+            .sample(self.source.system().processes().keys().map(|p| p.as_u32()));
 
         self.debug_stats_sys = Some(i.elapsed());
         let i = Instant::now();
 
-        self.refresh_procs();
-        self.debug_stats_state = Some(i.elapsed());
-        let i = Instant::now();
-
-        self.ram = read_memory(&mut self.sys);
+        self.ram = self.source.read_memory();
         self.debug_stats_ram = Some(i.elapsed());
         let i = Instant::now();
 
-        self.cpus = read_cpus(&mut self.sys);
+        self.cpus = self.source.read_cpus();
+        self.pressure = self.source.read_pressure();
+        self.psi_history.push(&self.pressure);
+        self.sensors = self.source.read_sensors();
+        self.sensor_history.push(&self.sensors);
+        self.power = self.source.read_power();
+        self.status = self.source.read_status();
         self.debug_stats_cpu = Some(i.elapsed());
+        let i = Instant::now();
+
+        self.refresh_sockets();
+        self.read_procs();
+        self.record_due = true;
+        self.refresh_procs();
+        self.refresh_maps();
+        self.refresh_cgroup_tree();
+        self.debug_stats_state = Some(i.elapsed());
     }
 
+    /// moves the replay along and shows its current frame, nothing is read from the system.
+    /// What isn't recorded is cleared so the header doesn't mix in the replaying box
+    fn refresh_replay(&mut self) {
+        self.pressure = PressureSet::default();
+        self.psi_history = PsiHistory::new();
+        self.sensors.clear();
+        self.sensor_history = SensorHistory::new();
+        self.power = PowerStatus::default();
+        self.cpu_details = CpuDetails::default();
+
+        let snapshot = self.replay.as_mut().and_then(|replay| {
+            replay.advance();
+            replay.snapshot()
+        });
+
+        match snapshot {
+            Some(snapshot) => {
+                self.all_processes = snapshot.processes;
+                self.cpus = snapshot.cpus;
+                self.ram = snapshot.ram;
+                self.info = snapshot.info;
+                self.status = snapshot.status;
+            }
+            None => self.all_processes.clear(),
+        }
+        self.refresh_procs();
+    }
+
+    /// reads every process for `refresh_procs` to filter and sort
+    fn read_procs(&mut self) {
+        self.all_processes = self.source.read_procs();
+        self.net_throughput.apply(&mut self.all_processes);
+    }
+
+    /// replay controls. Returns whether the key was handled
+    fn handle_replay_key(&mut self, code: KeyCode) -> bool {
+        let replay = match &mut self.replay {
+            Some(replay) => replay,
+            None => return false,
+        };
+
+        match code {
+            KeyCode::Char('.') => replay.toggle_pause(),
+            KeyCode::Char('[') => replay.step(false),
+            KeyCode::Char(']') => replay.step(true),
+            KeyCode::Char('{') => replay.seek(-60),
+            KeyCode::Char('}') => replay.seek(60),
+            KeyCode::Char('-') => replay.slower(),
+            KeyCode::Char('+') => replay.faster(),
+            _ => return false,
+        }
+
+        self.refresh_replay();
+        true
+    }
+
+    /// keys that kill, pause or limit live processes and cgroups. During a replay they would hit
+    /// whatever has the recorded pids now, so they're ignored
+    fn is_live_action(&self, code: KeyCode) -> bool {
+        match self.current_screen {
+            CurrentScreen::Main => matches!(
+                code,
+                KeyCode::Char('K') | KeyCode::Char('P') | KeyCode::Char('R') | KeyCode::Char('L')
+            ),
+            CurrentScreen::Cgroups => {
+                matches!(code, KeyCode::Char('P') | KeyCode::Char('R'))
+            }
+            _ => false,
+        }
+    }
+
+    /// appends the processes just read to the recording, stops recording when that fails
+    fn record(&mut self) {
+        let recorder = match &mut self.recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        let written = recorder.write(
+            unix_now(),
+            &self.processes,
+            &self.cpus,
+            &self.ram,
+            &self.info,
+            &self.status,
+        );
+        if let Err(e) = written {
+            self.record_error = Some(e.to_string());
+            self.recorder = None;
+        }
+    }
+
+    /// walking every process' fds is expensive so sockets are only read while the ports screen
+    /// is open or the filter needs them. They aren't recorded so a replay has none
+    fn refresh_sockets(&mut self) {
+        let filter_needs_sockets = parse_filter(&self.filter).iter().any(|t| t.needs_sockets());
+        let wanted = matches!(self.current_screen, CurrentScreen::Ports) || filter_needs_sockets;
+
+        if wanted && self.replay.is_none() {
+            self.sockets = self.source.read_sockets();
+            self.sockets
+                .sort_by_key(|s| (s.protocol, s.local_port, s.local.clone()));
+        } else {
+            self.sockets.clear();
+        }
+    }
+
+    /// maps are only read for the process shown in the maps tab of the process info popup, and
+    /// never for the pids of a replay
+    fn refresh_maps(&mut self) {
+        match (
+            &self.current_screen,
+            self.proc_info_tab,
+            self.current_pid_watch,
+        ) {
+            (CurrentScreen::ProcInfo, ProcInfoTab::Maps, Some(pid)) if self.replay.is_none() => {
+                self.maps = self.source.read_maps(pid);
+                self.maps.sort_by_key(|m| Reverse(m.rss.unwrap_or(0)));
+            }
+            _ => self.maps.clear(),
+        }
+    }
+
+    /// every group has to be walked so the tree is only read while the cgroups screen is open. It
+    /// isn't recorded so a replay shows none
+    fn refresh_cgroup_tree(&mut self) {
+        match (&self.current_screen, self.source.cgroup_root()) {
+            (CurrentScreen::Cgroups, Some(root)) if self.replay.is_none() => {
+                self.cgroup_tree.refresh_from(&root, Instant::now())
+            }
+            _ => self.cgroup_tree.clear(),
+        }
+    }
+
+    /// number of rows shown in the maps tab
+    fn maps_rows(&self) -> usize {
+        if self.maps_grouped {
+            group_by_file(&self.maps).len()
+        } else {
+            self.maps.len()
+        }
+    }
+
+    /// filters and sorts the processes read by the last refresh, called on every key that
+    /// changes what the table shows
     fn refresh_procs(&mut self) {
-        self.processes = read_procs(&mut self.sys, &mut self.users);
+        self.processes = self.all_processes.clone();
+        if std::mem::take(&mut self.record_due) {
+            self.record();
+        }
+        self.pause_tracker.prune(&self.processes);
+        self.problems =
+            self.problem_tracker
+                .update(&self.processes, Instant::now(), self.disk_sleep_threshold);
+        let replay_time = self.replay.as_ref().and_then(|r| r.time());
+        let now = replay_time.unwrap_or_else(unix_now);
+        self.event_log.update(&self.processes, now);
+        self.crash_loops = detect_crash_loops(&self.event_log.events, now, self.crash_loop_rule);
+        for event in self
+            .alerts
+            .evaluate(&self.processes, &self.ram, &self.cpus, now)
+        {
+            // a replay only shows what would have fired
+            if replay_time.is_some() {
+                continue;
+            }
+            self.alerts.run_hook(&event);
+
+            let proc = event
+                .pid
+                .and_then(|pid| self.processes.iter().find(|p| p.pid == pid));
+            if let Some(proc) = proc {
+                if let Some(entry) =
+                    self.alerts
+                        .act(&event, proc, self.source.system(), &self.actor)
+                {
+                    self.audit_log.record(entry);
+                }
+            }
+        }
+
+        // the pids of a replay belong to another time so nothing more is read for them
+        if replay_time.is_none() {
+            // smaps_rollup makes the kernel walk the page tables of the process so only read it
+            // for everything when the table actually shows something other than rss
+            if self.memory_metric != MemoryMetric::Rss {
+                for p in self.processes.iter_mut() {
+                    p.memory_detail = self.source.read_proc_memory(p.pid);
+                }
+            } else if let Some(pid) = self.current_pid_watch {
+                if let Some(p) = self.processes.iter_mut().find(|p| p.pid == pid) {
+                    p.memory_detail = self.source.read_proc_memory(pid);
+                }
+            }
+        }
 
-        if self.filter.len() > 0 {
-            self.processes.retain(|p| {
-                let name = p.name.clone().unwrap_or(String::new());
-                let pid = p.pid.to_string();
-                name.starts_with(&self.filter) || pid.starts_with(&self.filter)
+        if let CurrentScreen::Users = self.current_screen {
+            // summed before filtering so the screen always covers the whole box, and after
+            // memory_detail is read so pss, uss and swap aren't all 0
+            self.user_summaries = group_procs(&self.processes, GroupBy::User, self.memory_metric);
+            self.user_summaries.sort_by(|a, b| {
+                b.cpu_usage
+                    .partial_cmp(&a.cpu_usage)
+                    .unwrap_or(std::cmp::Ordering::Greater)
             });
+            self.sessions = match replay_time {
+                Some(_) => None,
+                None => self.source.read_sessions(),
+            };
+        }
+
+        self.cgroup_pressure = match (&self.current_screen, self.current_pid_watch) {
+            (CurrentScreen::ProcInfo, Some(pid)) if replay_time.is_none() => {
+                self.source.read_cgroup_pressure(pid)
+            }
+            _ => None,
+        };
+
+        let terms = parse_filter(&self.filter);
+        if !terms.is_empty() {
+            if self.sockets.is_empty() && terms.iter().any(|t| t.needs_sockets()) {
+                self.refresh_sockets();
+            }
+
+            let ctx = FilterContext {
+                sockets: &self.sockets,
+            };
+            self.processes
+                .retain(|p| terms.iter().all(|t| t.matches(p, &ctx)));
         }
 
         match self.process_sort_strategy {
@@ -274,95 +1116,551 @@ impl State {
             }
             ProcessSortStrategy::Memory => {
                 // also want descending order, most memory usage goes first
-                self.processes.sort_by_key(|p| Reverse(p.memory));
+                let metric = self.memory_metric;
+                self.processes.sort_by_key(|p| Reverse(p.memory_by(metric)));
             }
+            // descending, processes without a known rate go last
+            ProcessSortStrategy::NetRx => self.processes.sort_by_key(|p| Reverse(p.net_rx)),
+            ProcessSortStrategy::NetTx => self.processes.sort_by_key(|p| Reverse(p.net_tx)),
             ProcessSortStrategy::Alphabetical => self.processes.sort_by_key(|p| p.name.clone()),
         }
+
+        self.groups = group_procs(&self.processes, self.group_by, self.memory_metric);
+        self.sort_groups();
+        self.build_rows();
     }
 
-    fn select_none(&mut self) {
-        self.processes_state.select(None);
+    /// sorts `groups` the same way `refresh_procs` sorts processes, using the group totals
+    fn sort_groups(&mut self) {
+        let processes = &self.processes;
+        let first = |g: &ProcGroup| &processes[g.members[0]];
+
+        match self.process_sort_strategy {
+            ProcessSortStrategy::User => self.groups.sort_by_key(|g| first(g).user.clone()),
+            ProcessSortStrategy::Pid => self.groups.sort_by_key(|g| g.min_pid),
+            ProcessSortStrategy::Ppid => self.groups.sort_by_key(|g| first(g).ppid),
+            ProcessSortStrategy::CpuUsage => self.groups.sort_by(|a, b| {
+                b.cpu_usage
+                    .partial_cmp(&a.cpu_usage)
+                    .unwrap_or(std::cmp::Ordering::Greater)
+            }),
+            ProcessSortStrategy::Memory => self.groups.sort_by_key(|g| Reverse(g.memory)),
+            ProcessSortStrategy::NetRx => self.groups.sort_by_key(|g| Reverse(g.net_rx)),
+            ProcessSortStrategy::NetTx => self.groups.sort_by_key(|g| Reverse(g.net_tx)),
+            ProcessSortStrategy::Alphabetical => self.groups.sort_by_key(|g| g.key.clone()),
+        }
     }
 
-    fn next_row(&mut self) {
-        let i = match self.processes_state.selected() {
-            Some(i) => {
-                if i >= self.processes.len() - 1 {
-                    0
+    /// rebuilds `rows` from `processes` and `groups`, expanded groups are followed by their
+    /// members
+    fn build_rows(&mut self) {
+        self.rows.clear();
+
+        if self.group_by == GroupBy::None {
+            self.rows
+                .extend((0..self.processes.len()).map(ProcRow::Proc));
+            return;
+        }
+
+        for (idx, group) in self.groups.iter().enumerate() {
+            self.rows.push(ProcRow::Group(idx));
+            if self.expanded_groups.contains(&group.key) {
+                self.rows
+                    .extend(group.members.iter().map(|m| ProcRow::Proc(*m)));
+            }
+        }
+    }
+
+    pub fn selected_row(&self) -> Option<ProcRow> {
+        self.processes_state
+            .selected()
+            .and_then(|idx| self.rows.get(idx))
+            .copied()
+    }
+
+    /// the pids the kill confirm popup is about, as they were when it was opened
+    pub fn watched_pids(&self) -> Vec<u32> {
+        self.kill_targets.iter().map(|(pid, _)| *pid).collect()
+    }
+
+    /// selects the row of `pid` in the process table, clearing the filter if it's hiding it and
+    /// expanding its group if it's collapsed
+    pub fn select_pid(&mut self, pid: u32) {
+        if !self.processes.iter().any(|p| p.pid == pid) {
+            self.filter.clear();
+            self.refresh_procs();
+        }
+
+        let proc_idx = match self.processes.iter().position(|p| p.pid == pid) {
+            Some(idx) => idx,
+            None => return self.processes_state.select(None),
+        };
+
+        if let Some(group) = self.groups.iter().find(|g| g.members.contains(&proc_idx)) {
+            self.expanded_groups.insert(group.key.clone());
+            self.build_rows();
+        }
+
+        let idx = self.rows.iter().position(|r| *r == ProcRow::Proc(proc_idx));
+        self.processes_state.select(idx);
+    }
+
+    /// filters to the processes sharing the pid namespace of the selected process, pressing it
+    /// again moves on to the next kind of namespace and clears the filter after the last one
+    fn filter_same_namespace(&mut self) {
+        let proc = match self.selected_row() {
+            Some(ProcRow::Proc(idx)) => &self.processes[idx],
+            _ => return,
+        };
+        let pid = proc.pid;
+
+        let kind = match parse_filter(&self.filter).as_slice() {
+            [FilterTerm::Namespace(kind, inode)] if proc.namespaces.get(*kind) == Some(*inode) => {
+                kind.next()
+            }
+            _ => Some(NsKind::Pid),
+        };
+
+        // skip namespaces that can't be read, i.e. other users' processes without root
+        let next = std::iter::successors(kind, |k| k.next())
+            .find_map(|k| proc.namespaces.get(k).map(|inode| (k, inode)));
+
+        self.filter = match next {
+            Some((kind, inode)) => format!("ns:{}:{}", kind, inode),
+            None => String::new(),
+        };
+        self.refresh_procs();
+        self.select_pid(pid);
+    }
+
+    /// changes what counts as a crash loop and detects them again with the new rule
+    fn set_crash_loop_rule(&mut self, change: impl FnOnce(&mut CrashLoopRule)) {
+        change(&mut self.crash_loop_rule);
+        let now = self
+            .replay
+            .as_ref()
+            .and_then(|r| r.time())
+            .unwrap_or_else(unix_now);
+        self.crash_loops = detect_crash_loops(&self.event_log.events, now, self.crash_loop_rule);
+        self.crash_loops_state.select(None);
+    }
+
+    /// quits right away unless something is still paused, then asks whether to resume it first
+    fn quit(&mut self) {
+        if self.pause_tracker.is_empty() {
+            self.exit = true;
+        } else {
+            self.current_screen = CurrentScreen::ResumeConfirm;
+        }
+    }
+
+    /// what pausing or limiting the selected row acts on, see `SelectedTarget`
+    fn selected_target(&self) -> Option<SelectedTarget> {
+        let (label, members) = match self.selected_row()? {
+            ProcRow::Proc(idx) => (format!("pid {}", self.processes[idx].pid), vec![idx]),
+            ProcRow::Group(idx) => {
+                let group = &self.groups[idx];
+                (
+                    format!("{} {}", self.group_by, group.key),
+                    group.members.clone(),
+                )
+            }
+        };
+
+        let shared_cgroup = match self.selected_row() {
+            Some(ProcRow::Group(_)) if self.group_by == GroupBy::Cgroup => {
+                let mut paths = members.iter().map(|m| {
+                    self.processes[*m]
+                        .cgroup
+                        .as_ref()
+                        .and_then(|c| c.v2_path.clone())
+                });
+                let first = paths.next().flatten();
+                first.filter(|f| f != "/" && paths.all(|p| p.as_ref() == Some(f)))
+            }
+            _ => None,
+        };
+
+        Some(SelectedTarget {
+            label,
+            pids: members
+                .iter()
+                .map(|m| (self.processes[*m].pid, self.processes[*m].start_time))
+                .collect(),
+            shared_cgroup,
+        })
+    }
+
+    /// pauses (or resumes) the selected process with SIGSTOP (SIGCONT) or every member of the
+    /// selected group. Groups of processes that all share a cgroup are frozen with cgroup.freeze
+    /// instead so processes forked after the pause are caught too
+    fn pause_selected(&mut self, pause: bool) {
+        let target = match self.selected_target() {
+            Some(target) => target,
+            None => return,
+        };
+
+        // the throttler would keep continuing a paused process, including through the limit of a
+        // group the process is in
+        if pause {
+            self.throttler.unlimit(&target.label, self.source.system());
+            let pids: Vec<u32> = target.pids.iter().map(|(pid, _)| *pid).collect();
+            self.throttler.unlimit_pids(&pids, self.source.system());
+        }
+
+        if let Some(path) = target.shared_cgroup {
+            self.freeze_cgroup(&path, pause);
+        } else {
+            for (pid, _) in target.pids {
+                let audit_target = self.audit_target(pid);
+                if pause {
+                    let ok = self.pause_tracker.pause(self.source.system(), pid);
+                    self.record_action("pause", &audit_target, "signal STOP", ok_or_failed(ok));
                 } else {
-                    i + 1
+                    let ok = self.pause_tracker.resume(self.source.system(), pid);
+                    self.record_action("resume", &audit_target, "signal CONT", ok_or_failed(ok));
                 }
             }
-            None => 0,
+        }
+
+        self.refresh_procs();
+    }
+
+    /// opens the limit popup filled in with the current limit of the selected row, if any
+    fn open_limit(&mut self) {
+        let target = match self.selected_target() {
+            Some(target) => target,
+            None => return,
         };
-        self.processes_state.select(Some(i));
+
+        let current = self.throttler.target_of(&target.label).or_else(|| {
+            target
+                .shared_cgroup
+                .as_deref()
+                .and_then(|path| self.throttler.target_of(path))
+        });
+        self.limit_input = current.map_or(String::new(), |t| format!("{:.0}", t));
+        self.limit_label = Some(target.label);
+        self.current_screen = CurrentScreen::Limit;
     }
 
-    fn previous_row(&mut self) {
-        let i = match self.processes_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.processes.len() - 1
+    /// applies `limit_input` to the selected row, nothing or 0 removes the limit. Groups sharing
+    /// a cgroup get cpu.max written when it's writable, everything else is duty cycled
+    fn apply_limit(&mut self) {
+        let target = match self.selected_target() {
+            Some(target) if Some(&target.label) == self.limit_label.as_ref() => target,
+            // the selection moved or disappeared since the popup was opened
+            _ => return,
+        };
+        let percent = self.limit_input.parse::<f32>().unwrap_or(0.0);
+        let cgroup_limit = target
+            .shared_cgroup
+            .as_deref()
+            .and_then(|path| self.throttler.target_of(path));
+        let signal_limit = self.throttler.target_of(&target.label);
+
+        if let Some(path) = &target.shared_cgroup {
+            let result = self.throttler.unlimit_cgroup(path);
+            if cgroup_limit.is_some() && percent <= 0.0 {
+                self.record_action(
+                    "limit",
+                    &cgroup_target(path),
+                    "cpu.max max",
+                    result_of(result),
+                );
+            }
+        }
+        self.throttler.unlimit(&target.label, self.source.system());
+        if signal_limit.is_some() && percent <= 0.0 {
+            for (pid, _) in &target.pids {
+                let audit_target = self.audit_target(*pid);
+                self.record_action("limit", &audit_target, "unlimit", String::from("ok"));
+            }
+        }
+
+        if percent <= 0.0 {
+            return;
+        }
+
+        let by_cgroup = match (&target.shared_cgroup, self.source.cgroup_root()) {
+            (Some(path), Some(root)) => self.throttler.limit_cgroup(&root, path, percent).is_ok(),
+            _ => false,
+        };
+
+        let action = format!("limit {:.0}%", percent);
+        if by_cgroup {
+            let path = target.shared_cgroup.as_deref().unwrap_or_default();
+            self.record_action("limit", &cgroup_target(path), &action, String::from("ok"));
+        } else {
+            for (pid, _) in &target.pids {
+                let audit_target = self.audit_target(*pid);
+                // chadtop never throttles itself, see `Throttler::limit`
+                let result = if *pid == std::process::id() {
+                    String::from("failed: chadtop itself")
                 } else {
-                    i - 1
-                }
+                    String::from("ok")
+                };
+                self.record_action("limit", &audit_target, &action, result);
             }
-            None => 0,
+            self.throttler.limit(&target.label, target.pids, percent);
+        }
+    }
+
+    fn freeze_cgroup(&mut self, path: &str, freeze: bool) {
+        let root = match self.source.cgroup_root() {
+            Some(root) => root,
+            None => return,
+        };
+
+        // failures (no permission, no freezer) leave the group as it was, which the cgroups
+        // screen and the state column already show
+        let (source, action, result) = if freeze {
+            let own = read_cgroup(std::process::id()).and_then(|c| c.v2_path);
+            let result = self.pause_tracker.freeze(&root, path, own.as_deref());
+            ("freeze", "cgroup.freeze 1", result)
+        } else {
+            (
+                "thaw",
+                "cgroup.freeze 0",
+                self.pause_tracker.thaw(&root, path),
+            )
         };
-        self.processes_state.select(Some(i));
+        self.record_action(source, &cgroup_target(path), action, result_of(result));
+    }
+
+    /// the process `pid` as the audit log should describe it, with only its pid once it's gone
+    fn audit_target(&self, pid: u32) -> Proc {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .cloned()
+            .unwrap_or(Proc {
+                pid,
+                ..Default::default()
+            })
+    }
+
+    /// adds an entry for something chadtop was asked to do from the ui
+    fn record_action(&mut self, source: &str, target: &Proc, action: &str, result: String) {
+        let mut entry =
+            AuditEntry::new(unix_now(), &self.actor, source, target, action.to_string());
+        entry.result = result;
+        self.audit_log.record(entry);
+    }
+
+    fn select_none(&mut self) {
+        self.processes_state.select(None);
+    }
+
+    fn next_row(&mut self) {
+        next_row_in(&mut self.processes_state, self.rows.len());
+    }
+
+    fn previous_row(&mut self) {
+        previous_row_in(&mut self.processes_state, self.rows.len());
     }
 
     fn first(&mut self) {
-        self.processes_state.select(Some(0));
+        first_in(&mut self.processes_state, self.rows.len());
     }
 
     fn last(&mut self) {
-        self.processes_state.select(Some(self.processes.len() - 1));
+        last_in(&mut self.processes_state, self.rows.len());
     }
 }
 
-fn get_refresh_kind() -> RefreshKind {
-    RefreshKind::nothing()
-        .with_memory(MemoryRefreshKind::everything())
-        .with_cpu(CpuRefreshKind::nothing().with_cpu_usage())
-        .with_processes(
-            ProcessRefreshKind::nothing()
-                .with_cmd(UpdateKind::Always)
-                .with_exe(UpdateKind::Always)
-                .with_cwd(UpdateKind::Always)
-                .with_memory()
-                .with_cpu()
-                .with_disk_usage()
-                .with_user(UpdateKind::Always)
-                .without_tasks(),
-        )
+/// cgroup actions don't act on a single process, the audit log gets the path in place of a name
+fn cgroup_target(path: &str) -> Proc {
+    Proc {
+        pid: 0,
+        name: Some(path.to_string()),
+        ..Default::default()
+    }
+}
+
+fn ok_or_failed(ok: bool) -> String {
+    String::from(if ok { "ok" } else { "failed" })
+}
+
+fn result_of(result: std::io::Result<()>) -> String {
+    match result {
+        Ok(()) => String::from("ok"),
+        Err(e) => format!("failed: {}", e),
+    }
+}
+
+/// moves the selection of a table with `len` rows down one, wrapping around to the top
+fn next_row_in(table_state: &mut TableState, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    let i = match table_state.selected() {
+        Some(i) => {
+            if i >= len - 1 {
+                0
+            } else {
+                i + 1
+            }
+        }
+        None => 0,
+    };
+    table_state.select(Some(i));
+}
+
+/// moves the selection of a table with `len` rows up one, wrapping around to the bottom
+fn previous_row_in(table_state: &mut TableState, len: usize) {
+    if len == 0 {
+        return;
+    }
+
+    let i = match table_state.selected() {
+        Some(i) => {
+            if i == 0 {
+                len - 1
+            } else {
+                i - 1
+            }
+        }
+        None => 0,
+    };
+    table_state.select(Some(i));
+}
+
+fn first_in(table_state: &mut TableState, len: usize) {
+    if len > 0 {
+        table_state.select(Some(0));
+    }
+}
+
+fn last_in(table_state: &mut TableState, len: usize) {
+    if len > 0 {
+        table_state.select(Some(len - 1));
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::maps::Mapping;
+    use crate::proc_mem::ProcMemory;
+    use crate::record::Recording;
+    use crate::source::FixtureSource;
+    use crate::test_util::temp_path;
     use ratatui::crossterm::event::KeyCode;
     use ratatui::crossterm::event::KeyEvent;
 
     #[test]
     fn test_state_initializes_correctly() {
-        let state = State::new(false);
+        let state = fixture_state();
 
-        assert!(
-            !state.processes.is_empty(),
-            "Expected some processes to be loaded"
-        );
-        assert!(!state.cpus.is_empty(), "Expected CPU info to be loaded");
-        assert!(state.ram.total > 0, "Expected non-zero total RAM");
-        assert!(
-            !state.info.kernel_long_version.is_empty(),
-            "Expected OS info to be loaded"
-        );
+        assert_eq!(pids(&state), vec![101, 103, 102]);
+        assert_eq!(state.cpus[0].usage, 25.0);
+        assert_eq!(state.ram.total, 16 * 1024 * 1024 * 1024);
+        assert_eq!(state.info.kernel_long_version, "Linux 6.0.0-fixture");
+        assert_eq!(state.status.process_count, 3);
+        assert_eq!(state.cgroup_tree.nodes.len(), 0);
+        assert!(state.alerts.rules.is_empty());
+        assert_eq!(state.audit_log.path, None);
+    }
+
+    fn fixture_state() -> State {
+        let proc = |pid: u32, name: &str, user: &str, cpu_usage: f32, memory: u64| Proc {
+            pid,
+            ppid: Some(1),
+            name: Some(String::from(name)),
+            user: Some(String::from(user)),
+            cpu_usage,
+            memory,
+            ..Default::default()
+        };
+
+        State::with_source(
+            false,
+            Box::new(FixtureSource::new(vec![
+                proc(101, "postgres", "alice", 50.0, 100),
+                proc(102, "nginx", "bob", 5.0, 300),
+                proc(103, "bash", "alice", 20.0, 200),
+            ])),
+        )
+    }
+
+    fn pids(state: &State) -> Vec<u32> {
+        state.processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn test_fixture_state_sorts_deterministically() {
+        let mut state = fixture_state();
+
+        assert_eq!(state.info.host_name.as_deref(), Some("fixture"));
+        assert_eq!(state.cpus.len(), 5);
+        assert_eq!(pids(&state), vec![101, 103, 102]);
+
+        // cpu usage -> name -> memory
+        state.handle_key(&KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(pids(&state), vec![103, 102, 101]);
+        state.handle_key(&KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(pids(&state), vec![102, 103, 101]);
+    }
+
+    #[test]
+    fn test_keys_dont_read_processes_again() {
+        let mut state = fixture_state();
+        state.source = Box::new(FixtureSource::new(vec![Proc {
+            pid: 104,
+            ..Default::default()
+        }]));
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('s')));
+        assert_eq!(pids(&state), vec![103, 102, 101]);
+
+        state.refresh();
+        assert_eq!(pids(&state), vec![104]);
+    }
+
+    #[test]
+    fn test_fixture_state_filters_and_selects() {
+        let mut state = fixture_state();
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('f')));
+        for c in "user:alice".chars() {
+            state.handle_key(&KeyEvent::from(KeyCode::Char(c)));
+        }
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        assert_eq!(pids(&state), vec![101, 103]);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('G')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('d')));
+        assert!(matches!(state.current_screen, CurrentScreen::ProcInfo));
+        assert_eq!(state.current_pid_watch, Some(103));
+        state.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        state.handle_key(&KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(pids(&state), vec![101, 103, 102]);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('a')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('a')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('a')));
+        assert_eq!(state.group_by, GroupBy::User);
+        assert_eq!(state.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_fixture_processes_are_never_killed() {
+        let mut state = fixture_state();
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('K')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('y')));
+
+        assert_eq!(state.audit_log.entries[0].pid, 101);
+        assert_eq!(state.audit_log.entries[0].result, "failed: process exited");
     }
 
     #[test]
     fn test_sort_strategy_cycles_through_all_variants() {
-        let mut state = State::new(false);
+        let mut state = fixture_state();
         let original = &state.process_sort_strategy;
 
         let mut seen = std::collections::HashSet::new();
@@ -373,12 +1671,12 @@ mod tests {
             seen.insert(format!("{}", state.process_sort_strategy));
         }
 
-        assert_eq!(seen.len(), 6);
+        assert_eq!(seen.len(), 8);
     }
 
     #[test]
     fn test_quit_key_sets_exit_true() {
-        let mut state = State::new(false);
+        let mut state = fixture_state();
         assert!(!state.exit);
         state.handle_key(&KeyEvent::from(KeyCode::Char('q')));
         assert!(state.exit, "Expected 'q' to set exit flag");
@@ -386,30 +1684,647 @@ mod tests {
 
     #[test]
     fn test_handle_key_s_changes_sort_strategy() {
-        let mut state = State::new(false);
+        let mut state = fixture_state();
         let before = format!("{}", state.process_sort_strategy);
         state.handle_key(&KeyEvent::from(KeyCode::Char('s')));
         let after = format!("{}", state.process_sort_strategy);
         assert_ne!(before, after, "Sort strategy should change on 's' key");
+        assert_eq!(pids(&state), vec![103, 102, 101]);
     }
 
     #[test]
     fn test_sysinfo_screen() {
-        let mut state = State::new(false);
-        let before = state.current_screen.clone();
-        assert!(matches!(before, CurrentScreen::Main));
+        let mut state = fixture_state();
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
         state.handle_key(&KeyEvent::from(KeyCode::Char('i')));
-        let after = state.current_screen.clone();
-        assert!(matches!(after, CurrentScreen::SysInfo));
+        assert!(matches!(state.current_screen, CurrentScreen::SysInfo));
+        assert_eq!(state.status.uptime, 3600);
     }
 
     #[test]
-    fn test_help_screen() {
+    fn test_memory_metric_key_cycles() {
+        let mut state = fixture_state();
+        assert_eq!(state.memory_metric, MemoryMetric::Rss);
+        state.handle_key(&KeyEvent::from(KeyCode::Char('m')));
+        assert_eq!(state.memory_metric, MemoryMetric::Pss);
+    }
+
+    #[test]
+    fn test_proc_info_tab_switches_to_maps() {
+        let mut state = fixture_state();
+        state.select_pid(103);
+        state.handle_key(&KeyEvent::from(KeyCode::Char('d')));
+        assert!(matches!(state.current_screen, CurrentScreen::ProcInfo));
+        assert_eq!(state.proc_info_tab, ProcInfoTab::Overview);
+        assert_eq!(state.current_pid_watch, Some(103));
+
+        state.handle_key(&KeyEvent::from(KeyCode::Tab));
+
+        assert_eq!(state.proc_info_tab, ProcInfoTab::Maps);
+        assert!(state.maps.is_empty());
+    }
+
+    #[test]
+    fn test_problems_screen_threshold_keys() {
+        let mut state = fixture_state();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('z')));
+        assert!(matches!(state.current_screen, CurrentScreen::Problems));
+        assert!(state.problems.is_empty());
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('+')));
+        assert_eq!(state.disk_sleep_threshold, Duration::from_secs(15));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('-')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('-')));
+        assert_eq!(state.disk_sleep_threshold, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_crash_loops_rule_keys() {
+        let mut state = fixture_state();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('l')));
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('+')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('<')));
+
+        assert_eq!(state.crash_loop_rule.max_exits, 4);
+        assert_eq!(state.crash_loop_rule.window, 50);
+    }
+
+    #[test]
+    fn test_users_screen_sums_memory_detail() {
+        let mut state = fixture_state();
+        let proc = |pid: u32, pss: u64| Proc {
+            pid,
+            user: Some(String::from("alice")),
+            memory_detail: Some(ProcMemory {
+                pss: Some(pss),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        state.source = Box::new(FixtureSource::new(vec![proc(101, 1000), proc(103, 24)]));
+        state.refresh();
+        state.memory_metric = MemoryMetric::Pss;
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('u')));
+
+        assert_eq!(state.user_summaries[0].memory, 1024);
+    }
+
+    #[test]
+    fn test_users_screen_filters_unknown_owner() {
+        let mut state = fixture_state();
+        state.source = Box::new(FixtureSource::new(vec![Proc {
+            pid: 104,
+            ..Default::default()
+        }]));
+        state.refresh();
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('u')));
+        assert_eq!(state.user_summaries[0].key, "n/a");
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(state.filter, "user:n/a");
+        assert_eq!(pids(&state), vec![104]);
+    }
+
+    #[test]
+    fn test_events_screen() {
+        let mut state = fixture_state();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('e')));
+        assert!(matches!(state.current_screen, CurrentScreen::Events));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('e')));
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
+    }
+
+    #[test]
+    fn test_group_rows_expand_and_collapse() {
+        let mut state = fixture_state();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('a')));
+        assert_eq!(state.group_by, GroupBy::Name);
+        assert_eq!(state.rows.len(), 3);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        assert_eq!(state.rows.len(), 4);
+        assert_eq!(state.rows[1], ProcRow::Proc(state.groups[0].members[0]));
+        assert_eq!(state.processes[state.groups[0].members[0]].pid, 101);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        assert_eq!(state.rows.len(), 3);
+    }
+
+    #[test]
+    fn test_kill_confirm_on_group_targets_every_member() {
+        let mut state = fixture_state();
+        state.group_by = GroupBy::User;
+        state.refresh();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('K')));
+
+        assert!(matches!(state.current_screen, CurrentScreen::KillConfirm));
+        let mut watched = state.watched_pids();
+        watched.sort();
+        assert_eq!(watched, vec![101, 103]);
+        state.handle_key(&KeyEvent::from(KeyCode::Char('n')));
+        assert!(state.audit_log.entries.is_empty());
+    }
+
+    #[test]
+    fn test_kill_confirm_only_kills_what_was_shown() {
+        let mut state = fixture_state();
+        state.group_by = GroupBy::User;
+        state.refresh();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('K')));
+
+        // a new alice process and 103's pid reused while the popup is open
+        let proc = |pid: u32, name: &str, start_time: u64| Proc {
+            pid,
+            name: Some(String::from(name)),
+            user: Some(String::from("alice")),
+            start_time,
+            ..Default::default()
+        };
+        state.source = Box::new(FixtureSource::new(vec![
+            proc(101, "postgres", 0),
+            proc(103, "reused", 50),
+            proc(104, "new", 60),
+        ]));
+        state.refresh();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('y')));
+
+        let killed: Vec<(u32, Option<&str>)> = state
+            .audit_log
+            .entries
+            .iter()
+            .map(|e| (e.pid, e.name.as_deref()))
+            .collect();
+        assert_eq!(killed, vec![(101, Some("postgres")), (103, None)]);
+    }
+
+    #[test]
+    fn test_users_screen_applies_user_filter() {
+        let mut state = fixture_state();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('u')));
+        assert!(matches!(state.current_screen, CurrentScreen::Users));
+        let users: Vec<&str> = state
+            .user_summaries
+            .iter()
+            .map(|s| s.key.as_str())
+            .collect();
+        assert_eq!(users, vec!["alice", "bob"]);
+        assert_eq!(state.user_summaries[0].members.len(), 2);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
+        assert_eq!(state.filter, "user:alice");
+        assert_eq!(pids(&state), vec![101, 103]);
+        assert!(state.user_summaries.is_empty());
+    }
+
+    #[test]
+    fn test_sensors_screen() {
+        let mut state = fixture_state();
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('t')));
+        assert!(matches!(state.current_screen, CurrentScreen::Sensors));
+        assert!(state.sensors.is_empty());
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('t')));
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
+    }
+
+    #[test]
+    fn test_cgroups_screen_is_cleared_on_close() {
+        let mut state = fixture_state();
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('c')));
+        assert!(matches!(state.current_screen, CurrentScreen::Cgroups));
+        // the fixture has no cgroup root
+        assert!(state.cgroup_tree.nodes.is_empty());
+
+        state.handle_key(&KeyEvent::from(KeyCode::Esc));
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
+        assert!(state.cgroup_tree.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_alert_rule_fires_for_process() {
+        let mut state = fixture_state();
+        let path = temp_path("alerts");
+        std::fs::write(&path, "busy: proc postgres cpu > 40%\n").unwrap();
+        state.alerts = AlertEngine::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        state.refresh();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('!')));
+        assert!(matches!(state.current_screen, CurrentScreen::Alerts));
+        assert_eq!(state.alerts.firing().len(), 1);
+        assert_eq!(state.alerts.firing()[0].pid, Some(101));
+
+        // only processes that are still running can be jumped to, and fixture ones never are
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(state.current_screen, CurrentScreen::Alerts));
+    }
+
+    #[test]
+    fn test_kill_is_audited() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        let mut state = State::new(false);
+        state.refresh();
+        state.select_pid(pid);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('K')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('y')));
+        child.wait().unwrap();
+
+        let entry = state.audit_log.entries.back().unwrap();
+        assert_eq!(entry.pid, pid);
+        assert_eq!(entry.name.as_deref(), Some("sleep"));
+        assert_eq!(entry.action, "signal KILL");
+        assert_eq!(entry.result, "ok");
+        assert_eq!(entry.actor, state.actor);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('H')));
+        assert!(matches!(state.current_screen, CurrentScreen::Audit));
+    }
+
+    #[test]
+    fn test_pause_resume_and_limit_are_audited() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
         let mut state = State::new(false);
-        let before = state.current_screen.clone();
-        assert!(matches!(before, CurrentScreen::Main));
+        state.refresh();
+        state.select_pid(pid);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('P')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('R')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('L')));
+        for c in ['2', '0'] {
+            state.handle_key(&KeyEvent::from(KeyCode::Char(c)));
+        }
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('L')));
+        state.handle_key(&KeyEvent::from(KeyCode::Backspace));
+        state.handle_key(&KeyEvent::from(KeyCode::Backspace));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('P')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('q')));
+        state.handle_key(&KeyEvent::from(KeyCode::Char('y')));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        let entries: Vec<(&str, &str, &str)> = state
+            .audit_log
+            .entries
+            .iter()
+            .inspect(|e| {
+                assert_eq!(e.pid, pid);
+                assert_eq!(e.name.as_deref(), Some("sleep"));
+            })
+            .map(|e| (e.source.as_str(), e.action.as_str(), e.result.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("pause", "signal STOP", "ok"),
+                ("resume", "signal CONT", "ok"),
+                ("limit", "limit 20%", "ok"),
+                ("limit", "unlimit", "ok"),
+                ("pause", "signal STOP", "ok"),
+                ("resume", "signal CONT", "ok"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_freeze_failure_is_audited() {
+        let root = temp_path("audit_freeze");
+        let mut state = fixture_state();
+        state.freeze_cgroup("/system.slice/missing.service", true);
+        assert!(
+            state.audit_log.entries.is_empty(),
+            "the fixture has no cgroup root"
+        );
+
+        std::fs::create_dir_all(root.join("system.slice/job.service")).unwrap();
+        let mut source = FixtureSource::new(Vec::new());
+        source.cgroup_root = Some(root.clone());
+        state.source = Box::new(source);
+        state.freeze_cgroup("/system.slice/job.service", true);
+        state.freeze_cgroup("/system.slice/missing.service", true);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let entries: Vec<(&str, Option<&str>, &str)> = state
+            .audit_log
+            .entries
+            .iter()
+            .map(|e| (e.source.as_str(), e.name.as_deref(), e.action.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "freeze",
+                    Some("/system.slice/job.service"),
+                    "cgroup.freeze 1"
+                ),
+                (
+                    "freeze",
+                    Some("/system.slice/missing.service"),
+                    "cgroup.freeze 1"
+                ),
+            ]
+        );
+        assert_eq!(state.audit_log.entries[0].result, "ok");
+        assert!(state.audit_log.entries[1].result.starts_with("failed: "));
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = temp_path("replay");
+        let _ = std::fs::remove_file(&path);
+        let mut state = fixture_state();
+        state.recorder = Some(Recorder::create(&path).unwrap());
+        state.refresh();
+        state.refresh();
+
+        let mut replaying = State::with_source(false, Box::new(FixtureSource::new(Vec::new())));
+        replaying.replay = Some(Replay::new(Recording::open(&path).unwrap()));
+        std::fs::remove_file(&path).unwrap();
+        replaying.refresh();
+
+        assert_eq!(pids(&replaying), vec![101, 103, 102]);
+        assert_eq!(replaying.info.host_name.as_deref(), Some("fixture"));
+        assert_eq!(replaying.replay.as_ref().unwrap().recording.len(), 2);
+        assert!(!replaying.pressure.is_available());
+        assert!(replaying.power.batteries.is_empty());
+
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char(']')));
+        let replay = replaying.replay.as_ref().unwrap();
+        assert!(replay.paused);
+        assert_eq!(replay.frame, 1);
+
+        // killing from a replay would hit whatever has the pid now
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('K')));
+        assert!(matches!(replaying.current_screen, CurrentScreen::Main));
+
+        // and so would freezing a cgroup
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('c')));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('P')));
+        assert!(replaying.pause_tracker.is_empty());
+    }
+
+    #[test]
+    fn test_replay_doesnt_read_unrecorded_screens() {
+        let path = temp_path("replay_screens");
+        let _ = std::fs::remove_file(&path);
+        let mut state = fixture_state();
+        state.recorder = Some(Recorder::create(&path).unwrap());
+        state.refresh();
+
+        let root = temp_path("replay_cgroups");
+        std::fs::create_dir_all(root.join("system.slice")).unwrap();
+        std::fs::write(root.join("cgroup.procs"), "").unwrap();
+        let mut source = FixtureSource::new(Vec::new());
+        source.sockets = vec![Socket {
+            protocol: crate::net::Protocol::Tcp,
+            local: String::from("0.0.0.0"),
+            local_port: Some(5432),
+            remote: None,
+            state: String::from("LISTEN"),
+            inode: 1,
+            pid: Some(101),
+            process_name: Some(String::from("postgres")),
+        }];
+        source.maps = vec![Mapping {
+            start: 0,
+            end: 4096,
+            perms: String::from("r-xp"),
+            path: Some(String::from("/usr/bin/postgres")),
+            rss: Some(4096),
+        }];
+        source.sessions = Some(HashMap::from([(String::from("alice"), 1)]));
+        source.cgroup_root = Some(root.clone());
+        let mut replaying = State::with_source(false, Box::new(source));
+
+        // the source has all of it, as a live session would show
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('p')));
+        let live_sockets = replaying.sockets.len();
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('c')));
+        let live_cgroups = replaying.cgroup_tree.nodes.len();
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        replaying.replay = Some(Replay::new(Recording::open(&path).unwrap()));
+        std::fs::remove_file(&path).unwrap();
+        replaying.refresh();
+
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('p')));
+        replaying.refresh();
+        assert!(replaying.sockets.is_empty());
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('c')));
+        replaying.refresh();
+        assert!(replaying.cgroup_tree.nodes.is_empty());
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('d')));
+        replaying.handle_key(&KeyEvent::from(KeyCode::Tab));
+        replaying.refresh();
+        assert_eq!(replaying.proc_info_tab, ProcInfoTab::Maps);
+        assert!(replaying.maps.is_empty());
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        replaying.handle_key(&KeyEvent::from(KeyCode::Char('u')));
+        assert_eq!(replaying.sessions, None);
+        replaying.handle_key(&KeyEvent::from(KeyCode::Esc));
+
+        replaying.filter = String::from("port:5432");
+        replaying.refresh();
+
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(live_sockets, 1);
+        assert!(live_cgroups > 0);
+        assert!(replaying.processes.is_empty());
+    }
+
+    #[test]
+    fn test_same_namespace_filter_cycles_kinds() {
+        let mut state = fixture_state();
+        let ns = |pid, net, mnt, uts, ipc| Namespaces {
+            pid: Some(pid),
+            net: Some(net),
+            mnt: Some(mnt),
+            user: Some(1),
+            uts,
+            ipc: Some(ipc),
+        };
+        let mut procs = state.all_processes.clone();
+        for p in procs.iter_mut() {
+            p.namespaces = match p.pid {
+                101 => ns(1, 1, 1, None, 1),
+                102 => ns(2, 2, 2, Some(2), 2),
+                _ => ns(1, 2, 1, Some(1), 2),
+            };
+        }
+        state.source = Box::new(FixtureSource::new(procs));
+        state.refresh();
+        state.select_pid(101);
+
+        let mut seen = Vec::new();
+        for _ in 0..6 {
+            state.handle_key(&KeyEvent::from(KeyCode::Char('n')));
+            seen.push((state.filter.clone(), pids(&state)));
+        }
+
+        // uts can't be read for 101 so it's skipped
+        let expected = [
+            ("ns:pid:1", vec![101, 103]),
+            ("ns:net:1", vec![101]),
+            ("ns:mnt:1", vec![101, 103]),
+            ("ns:user:1", vec![101, 103, 102]),
+            ("ns:ipc:1", vec![101]),
+            ("", vec![101, 103, 102]),
+        ];
+        let expected: Vec<(String, Vec<u32>)> = expected
+            .into_iter()
+            .map(|(filter, pids)| (String::from(filter), pids))
+            .collect();
+        assert_eq!(seen, expected);
+        assert!(matches!(
+            state.selected_row(),
+            Some(ProcRow::Proc(idx)) if state.processes[idx].pid == 101
+        ));
+    }
+
+    #[test]
+    fn test_quit_asks_to_resume_paused_processes() {
+        let mut state = State::new(false);
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        state.refresh();
+        state.select_pid(child.id());
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('P')));
+        let paused = state.pause_tracker.pid_count();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('q')));
+        let screen = state.current_screen.clone();
+        state.handle_key(&KeyEvent::from(KeyCode::Char('y')));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(paused, 1);
+        assert!(matches!(screen, CurrentScreen::ResumeConfirm));
+        assert!(state.exit);
+        assert!(state.pause_tracker.is_empty());
+    }
+
+    #[test]
+    fn test_limit_popup_sets_and_removes_limit() {
+        let mut state = State::new(false);
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        state.refresh();
+        state.select_pid(pid);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('L')));
+        let screen = state.current_screen.clone();
+        for c in ['2', '0'] {
+            state.handle_key(&KeyEvent::from(KeyCode::Char(c)));
+        }
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        let limited = state.throttler.target_for(pid, None);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('L')));
+        let prefilled = state.limit_input.clone();
+        state.handle_key(&KeyEvent::from(KeyCode::Backspace));
+        state.handle_key(&KeyEvent::from(KeyCode::Backspace));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        let removed = state.throttler.target_for(pid, None);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(matches!(screen, CurrentScreen::Limit));
+        assert_eq!(limited, Some(20.0));
+        assert_eq!(prefilled, "20");
+        assert_eq!(removed, None);
+    }
+
+    #[test]
+    fn test_ports_screen() {
+        let mut state = fixture_state();
+        let mut source = FixtureSource::new(state.processes.clone());
+        source.sockets = vec![Socket {
+            protocol: crate::net::Protocol::Tcp,
+            local: String::from("0.0.0.0"),
+            local_port: Some(5432),
+            remote: None,
+            state: String::from("LISTEN"),
+            inode: 1,
+            pid: Some(101),
+            process_name: Some(String::from("postgres")),
+        }];
+        state.source = Box::new(source);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('p')));
+        assert!(matches!(state.current_screen, CurrentScreen::Ports));
+        assert_eq!(state.sockets.len(), 1);
+
+        state.handle_key(&KeyEvent::from(KeyCode::Char('g')));
+        state.handle_key(&KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
+        let selected = state.processes_state.selected().unwrap();
+        assert_eq!(state.processes[selected].pid, 101);
+
+        state.refresh();
+        assert!(state.sockets.is_empty());
+    }
+
+    #[test]
+    fn test_select_pid_clears_hiding_filter() {
+        let mut state = fixture_state();
+        state.filter = String::from("definitely not a real process name");
+        state.refresh();
+        assert!(state.processes.is_empty());
+
+        state.select_pid(102);
+
+        assert!(state.filter.is_empty());
+        let selected = state
+            .processes_state
+            .selected()
+            .expect("expected a selection");
+        assert_eq!(state.processes[selected].pid, 102);
+    }
+
+    #[test]
+    fn test_help_screen() {
+        let mut state = fixture_state();
+        assert!(matches!(state.current_screen, CurrentScreen::Main));
         state.handle_key(&KeyEvent::from(KeyCode::Char('h')));
-        let after = state.current_screen.clone();
-        assert!(matches!(after, CurrentScreen::Help));
+        assert!(matches!(state.current_screen, CurrentScreen::Help));
     }
 }
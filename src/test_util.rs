@@ -0,0 +1,14 @@
+//! helpers shared by the tests of modules that read fixture files instead of /proc and /sys
+
+use std::{fs, path::Path, path::PathBuf};
+
+/// a path in the temp dir unique to `name` and this test run, nothing is created at it
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("chadtop_{}_{}", name, std::process::id()))
+}
+
+/// writes `content` to `path`, creating the directories leading up to it
+pub fn write_file(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
@@ -0,0 +1,444 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System};
+
+use crate::cgroup::in_cgroup;
+
+/// how often throttled processes are stopped and continued
+const PERIOD: Duration = Duration::from_millis(100);
+/// how many periods pass between measuring the usage of throttled processes
+const ADJUST_EVERY: u32 = 10;
+/// the period written to cpu.max, the kernel's default
+const CGROUP_PERIOD: u64 = 100_000;
+
+/// processes kept under a cpu target by stopping them for part of every `PERIOD`
+struct SignalLimit {
+    /// (pid, start time) so a pid reused by another process stops being throttled
+    pids: Vec<(u32, u64)>,
+    /// percent of one cpu, the same unit as the cpu usage column
+    target: f32,
+    /// fraction of every period the processes are allowed to run, adjusted until their usage
+    /// matches `target`
+    duty: f32,
+}
+
+/// a cgroup with its cpu.max set by chadtop
+struct CgroupLimit {
+    root: PathBuf,
+    target: f32,
+    /// cpu.max before chadtop touched it, written back when the limit is removed
+    previous: String,
+}
+
+/// limits the cpu usage of processes, either by duty cycling SIGSTOP and SIGCONT from a worker
+/// thread like cpulimit does or by writing cpu.max of a cgroup
+///
+/// dropping it removes every limit, continuing stopped processes and restoring cpu.max, so it
+/// has to live until chadtop exits (including on panic, which unwinds through `State`)
+pub struct Throttler {
+    limits: Arc<Mutex<HashMap<String, SignalLimit>>>,
+    cgroup_limits: HashMap<String, CgroupLimit>,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Default for Throttler {
+    fn default() -> Throttler {
+        Throttler::new()
+    }
+}
+
+impl Throttler {
+    pub fn new() -> Throttler {
+        Throttler {
+            limits: Arc::new(Mutex::new(HashMap::new())),
+            cgroup_limits: HashMap::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+            worker: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cgroup_limits.is_empty() && lock(&self.limits).is_empty()
+    }
+
+    /// keeps every process in `pids`, given as (pid, start time), together under `target` percent
+    /// of one cpu, replacing the limit called `key` if there is one
+    pub fn limit(&mut self, key: &str, pids: Vec<(u32, u64)>, target: f32) {
+        let pids: Vec<(u32, u64)> = pids
+            .into_iter()
+            .filter(|(pid, _)| *pid != std::process::id())
+            .collect();
+        if pids.is_empty() || target <= 0.0 {
+            return;
+        }
+
+        lock(&self.limits).insert(
+            key.to_string(),
+            SignalLimit {
+                pids,
+                target,
+                duty: (target / 100.0).clamp(0.01, 1.0),
+            },
+        );
+
+        if self.worker.is_none() {
+            let limits = Arc::clone(&self.limits);
+            let stop = Arc::clone(&self.stop);
+            self.worker = Some(thread::spawn(move || run_worker(limits, stop)));
+        }
+    }
+
+    /// removes the limit called `key`, continuing its processes. `sys` has to know about them
+    pub fn unlimit(&mut self, key: &str, sys: &System) {
+        // continued while holding the lock so the worker can't stop them again right after
+        let mut limits = lock(&self.limits);
+        if let Some(limit) = limits.remove(key) {
+            signal(sys, &limit.pids, Signal::Continue);
+        }
+    }
+
+    /// removes every limit covering any of `pids`, i.e. the group limit `name firefox` when one
+    /// firefox process is paused, continuing their processes
+    pub fn unlimit_pids(&mut self, pids: &[u32], sys: &System) {
+        let mut limits = lock(&self.limits);
+        limits.retain(|_, limit| {
+            let covered = limit.pids.iter().any(|(pid, _)| pids.contains(pid));
+            if covered {
+                signal(sys, &limit.pids, Signal::Continue);
+            }
+            !covered
+        });
+    }
+
+    /// sets cpu.max of the cgroup at `path` under the cgroup2 mount `root` to `target` percent
+    /// of one cpu
+    pub fn limit_cgroup(&mut self, root: &Path, path: &str, target: f32) -> io::Result<()> {
+        let file = cpu_max_file(root, path);
+        let previous = match self.cgroup_limits.get(path) {
+            Some(limit) => limit.previous.clone(),
+            None => fs::read_to_string(&file)?.trim().to_string(),
+        };
+
+        let quota = ((target / 100.0) as f64 * CGROUP_PERIOD as f64).max(1000.0) as u64;
+        fs::write(&file, format!("{} {}", quota, CGROUP_PERIOD))?;
+
+        self.cgroup_limits.insert(
+            path.to_string(),
+            CgroupLimit {
+                root: root.to_path_buf(),
+                target,
+                previous,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn unlimit_cgroup(&mut self, path: &str) -> io::Result<()> {
+        match self.cgroup_limits.remove(path) {
+            Some(limit) => fs::write(cpu_max_file(&limit.root, path), limit.previous),
+            None => Ok(()),
+        }
+    }
+
+    /// the target of the limit `key`, whether it's signal or cgroup based
+    pub fn target_of(&self, key: &str) -> Option<f32> {
+        lock(&self.limits)
+            .get(key)
+            .map(|l| l.target)
+            .or_else(|| self.cgroup_limits.get(key).map(|l| l.target))
+    }
+
+    /// the target of the limit covering `pid`, or the cgroup at `cgroup_path`
+    pub fn target_for(&self, pid: u32, cgroup_path: Option<&str>) -> Option<f32> {
+        let by_signal = lock(&self.limits)
+            .values()
+            .find(|l| l.pids.iter().any(|(p, _)| *p == pid))
+            .map(|l| l.target);
+
+        by_signal.or_else(|| {
+            let path = cgroup_path?;
+            self.cgroup_limits
+                .iter()
+                .find(|(group, _)| in_cgroup(path, group))
+                .map(|(_, l)| l.target)
+        })
+    }
+
+    /// removes every limit, see `Drop`
+    pub fn clear(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            // the worker continues everything it throttled on its way out, even if it panicked
+            let _ = worker.join();
+        }
+        lock(&self.limits).clear();
+        self.stop.store(false, Ordering::SeqCst);
+
+        for path in self.cgroup_limits.keys().cloned().collect::<Vec<String>>() {
+            let _ = self.unlimit_cgroup(&path);
+        }
+    }
+}
+
+impl Drop for Throttler {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// a panic while holding the lock shouldn't stop limits from being cleaned up
+fn lock(
+    limits: &Mutex<HashMap<String, SignalLimit>>,
+) -> MutexGuard<'_, HashMap<String, SignalLimit>> {
+    limits.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn cpu_max_file(root: &Path, path: &str) -> PathBuf {
+    root.join(path.trim_start_matches('/')).join("cpu.max")
+}
+
+fn signal(sys: &System, pids: &[(u32, u64)], signal: Signal) {
+    for (pid, _) in pids {
+        if let Some(process) = sys.process(Pid::from_u32(*pid)) {
+            process.kill_with(signal);
+        }
+    }
+}
+
+/// continues every throttled process when the worker exits, including by panicking
+struct ContinueOnExit(Arc<Mutex<HashMap<String, SignalLimit>>>);
+
+impl Drop for ContinueOnExit {
+    fn drop(&mut self) {
+        let limits = lock(&self.0);
+        let pids: Vec<(u32, u64)> = limits.values().flat_map(|l| l.pids.clone()).collect();
+
+        let mut sys = System::new();
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(
+                &pids
+                    .iter()
+                    .map(|(p, _)| Pid::from_u32(*p))
+                    .collect::<Vec<Pid>>(),
+            ),
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        signal(&sys, &pids, Signal::Continue);
+    }
+}
+
+fn run_worker(limits: Arc<Mutex<HashMap<String, SignalLimit>>>, stop: Arc<AtomicBool>) {
+    let _continue_on_exit = ContinueOnExit(Arc::clone(&limits));
+    let mut sys = System::new();
+    let mut tick: u32 = 0;
+
+    while !stop.load(Ordering::SeqCst) {
+        {
+            let mut limits = lock(&limits);
+            let unknown = limits
+                .values()
+                .flat_map(|l| l.pids.iter())
+                .any(|(pid, _)| sys.process(Pid::from_u32(*pid)).is_none());
+
+            if tick.is_multiple_of(ADJUST_EVERY) || unknown {
+                adjust(&mut sys, &mut limits, unknown);
+            }
+
+            for limit in limits.values() {
+                signal(&sys, &limit.pids, Signal::Continue);
+            }
+        }
+
+        let mut stops: Vec<(String, f32)> = lock(&limits)
+            .iter()
+            .filter(|(_, l)| l.duty < 1.0)
+            .map(|(key, l)| (key.clone(), l.duty))
+            .collect();
+        stops.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let mut elapsed = Duration::ZERO;
+        for (key, duty) in stops {
+            let at = PERIOD.mul_f32(duty);
+            thread::sleep(at.saturating_sub(elapsed));
+            elapsed = elapsed.max(at);
+
+            // the limit might have been removed while sleeping, its processes must stay running
+            if let Some(limit) = lock(&limits).get(&key) {
+                signal(&sys, &limit.pids, Signal::Stop);
+            }
+        }
+        thread::sleep(PERIOD.saturating_sub(elapsed));
+
+        tick = tick.wrapping_add(1);
+    }
+}
+
+/// refreshes the throttled processes, drops the ones that exited (or whose pid now belongs to
+/// another process) and moves every duty towards its target. Right after new processes show up
+/// their usage isn't known yet so it's left alone
+fn adjust(sys: &mut System, limits: &mut HashMap<String, SignalLimit>, first_refresh: bool) {
+    let pids: Vec<Pid> = limits
+        .values()
+        .flat_map(|l| l.pids.iter().map(|(p, _)| Pid::from_u32(*p)))
+        .collect();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_cpu(),
+    );
+
+    for limit in limits.values_mut() {
+        limit.pids.retain(|(pid, start_time)| {
+            sys.process(Pid::from_u32(*pid))
+                .is_some_and(|p| p.start_time() == *start_time)
+        });
+
+        let usage: f32 = limit
+            .pids
+            .iter()
+            .filter_map(|(pid, _)| sys.process(Pid::from_u32(*pid)))
+            .map(|p| p.cpu_usage())
+            .sum();
+
+        if !first_refresh && usage > 0.1 {
+            limit.duty = next_duty(limit.duty, usage, limit.target);
+        }
+    }
+
+    limits.retain(|_, l| !l.pids.is_empty());
+}
+
+/// usage scales with how long the processes get to run, so scale the duty by how far off the
+/// target the usage was. Halfway steps keep it from oscillating
+fn next_duty(duty: f32, usage: f32, target: f32) -> f32 {
+    let wanted = duty * target / usage;
+    ((duty + wanted) / 2.0).clamp(0.01, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_path;
+    use std::process::Command;
+
+    #[test]
+    fn test_next_duty_moves_towards_target() {
+        // running half the time used 100%, 25% needs a quarter of the time
+        let duty = next_duty(0.5, 100.0, 25.0);
+        assert!(duty < 0.5 && duty > 0.125);
+
+        // under the target lets it run longer, never past always running
+        assert!(next_duty(0.5, 10.0, 50.0) > 0.5);
+        assert_eq!(next_duty(1.0, 10.0, 50.0), 1.0);
+    }
+
+    #[test]
+    fn test_unlimit_pids_removes_covering_limits() {
+        let sys = System::new();
+        let mut throttler = Throttler::new();
+        throttler.limit("name firefox", vec![(42, 0), (43, 0)], 20.0);
+        throttler.limit("pid 43", vec![(43, 0)], 10.0);
+        throttler.limit("pid 44", vec![(44, 0)], 30.0);
+
+        throttler.unlimit_pids(&[43], &sys);
+
+        assert_eq!(throttler.target_for(42, None), None);
+        assert_eq!(throttler.target_for(43, None), None);
+        assert_eq!(throttler.target_for(44, None), Some(30.0));
+    }
+
+    fn start_time(pid: u32) -> u64 {
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+        sys.process(Pid::from_u32(pid)).unwrap().start_time()
+    }
+
+    #[test]
+    fn test_reused_pid_is_dropped() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut sys = System::new();
+        let mut limits = HashMap::from([(
+            String::from("pid"),
+            SignalLimit {
+                // started before it really did, as if the pid had been reused since
+                pids: vec![(pid, start_time(pid) - 1)],
+                target: 10.0,
+                duty: 0.1,
+            },
+        )]);
+
+        adjust(&mut sys, &mut limits, true);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(limits.is_empty());
+    }
+
+    #[test]
+    fn test_cgroup_limit_is_restored() {
+        let root = temp_path("cpu_max");
+        let group = root.join("system.slice/job.service");
+        fs::create_dir_all(&group).unwrap();
+        fs::write(group.join("cpu.max"), "max 100000\n").unwrap();
+
+        let mut throttler = Throttler::new();
+        throttler
+            .limit_cgroup(&root, "/system.slice/job.service", 50.0)
+            .unwrap();
+        let limited = fs::read_to_string(group.join("cpu.max")).unwrap();
+        let target = throttler.target_for(42, Some("/system.slice/job.service"));
+
+        drop(throttler);
+        let restored = fs::read_to_string(group.join("cpu.max")).unwrap();
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(limited, "50000 100000");
+        assert_eq!(target, Some(50.0));
+        assert_eq!(restored, "max 100000");
+    }
+
+    #[test]
+    fn test_dropping_continues_throttled_process() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let state = || {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+            let (_, rest) = stat.rsplit_once(") ").unwrap();
+            rest.chars().next().unwrap()
+        };
+
+        let mut throttler = Throttler::new();
+        throttler.limit("pid", vec![(pid, start_time(pid))], 1.0);
+        assert_eq!(throttler.target_for(pid, None), Some(1.0));
+
+        // a 1% duty keeps it stopped nearly the whole time
+        thread::sleep(PERIOD * 3);
+        let throttled: Vec<char> = (0..5)
+            .map(|_| {
+                thread::sleep(Duration::from_millis(10));
+                state()
+            })
+            .collect();
+        drop(throttler);
+        thread::sleep(Duration::from_millis(50));
+        let released = state();
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(throttled.contains(&'T'));
+        assert_ne!(released, 'T');
+    }
+}
@@ -3,15 +3,31 @@ use std::time::{Duration, Instant};
 use ratatui::{
     layout::{Constraint, Direction, Flex, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::{Line, Text},
+    text::{Line, Span, Text},
     widgets::{
-        block::Title, Block, Borders, Cell, Clear, HighlightSpacing, List, ListItem, Padding,
-        Paragraph, Row, Table,
+        block::Title, Block, Borders, Cell, Clear, Gauge, HighlightSpacing, List, ListItem,
+        Padding, Paragraph, Row, Sparkline, Table,
     },
     Frame,
 };
 
-use crate::state::{CurrentScreen, State};
+use crate::{
+    alerts::{AlertState, Unit},
+    clock::format_utc,
+    cpu::CpuUsage,
+    cpu_time::CpuBreakdown,
+    events::EventKind,
+    group::{GroupBy, ProcGroup},
+    maps::group_by_file,
+    namespace::NsKind,
+    pause::PauseKind,
+    problems::Problem,
+    proc::RunState,
+    proc_mem::MemoryMetric,
+    psi::PsiResource,
+    sensors::TempLevel,
+    state::{CurrentScreen, ProcInfoTab, ProcRow, ProcessSortStrategy, State},
+};
 
 /// handles ui for chadtop
 pub fn ui(frame: &mut Frame, state: &mut State) {
@@ -24,7 +40,7 @@ pub fn ui(frame: &mut Frame, state: &mut State) {
         .constraints([Constraint::Percentage(33), Constraint::Percentage(67)])
         .split(frame.area());
 
-    render_title(frame, chunks[0]);
+    render_title(frame, chunks[0], state);
 
     let body_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -35,6 +51,8 @@ pub fn ui(frame: &mut Frame, state: &mut State) {
         ])
         .split(chunks[1]);
 
+    render_banner(frame, body_chunks[0], state);
+
     render_filter(frame, body_chunks[1], state);
 
     render_proc_list(frame, body_chunks[2], state);
@@ -44,6 +62,17 @@ pub fn ui(frame: &mut Frame, state: &mut State) {
         CurrentScreen::SysInfo => render_sysinfo(frame, state),
         CurrentScreen::Help => render_help(frame),
         CurrentScreen::KillConfirm => render_killconfirm(frame, state),
+        CurrentScreen::Ports => render_ports(frame, state),
+        CurrentScreen::Problems => render_problems(frame, state),
+        CurrentScreen::Events => render_events(frame, state),
+        CurrentScreen::CrashLoops => render_crash_loops(frame, state),
+        CurrentScreen::Users => render_users(frame, state),
+        CurrentScreen::Sensors => render_sensors(frame, state),
+        CurrentScreen::Cgroups => render_cgroups(frame, state),
+        CurrentScreen::ResumeConfirm => render_resume_confirm(frame, state),
+        CurrentScreen::Limit => render_limit(frame, state),
+        CurrentScreen::Alerts => render_alerts(frame, state),
+        CurrentScreen::Audit => render_audit(frame, state),
         _ => {}
     }
 }
@@ -56,7 +85,7 @@ pub fn ui_debug(frame: &mut Frame, state: &mut State) {
         .constraints([Constraint::Percentage(33), Constraint::Percentage(67)])
         .split(frame.area());
 
-    render_title(frame, chunks[0]);
+    render_title(frame, chunks[0], state);
 
     let title_elapsed = i.elapsed();
     let i = Instant::now();
@@ -70,6 +99,13 @@ pub fn ui_debug(frame: &mut Frame, state: &mut State) {
         ])
         .split(chunks[1]);
 
+    let banner_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Fill(1), Constraint::Length(4)])
+        .split(body_chunks[0]);
+
+    render_banner(frame, banner_chunks[0], state);
+
     render_filter(frame, body_chunks[1], state);
 
     let filter_elapsed = i.elapsed();
@@ -85,6 +121,17 @@ pub fn ui_debug(frame: &mut Frame, state: &mut State) {
         CurrentScreen::SysInfo => render_sysinfo(frame, state),
         CurrentScreen::Help => render_help(frame),
         CurrentScreen::KillConfirm => render_killconfirm(frame, state),
+        CurrentScreen::Ports => render_ports(frame, state),
+        CurrentScreen::Problems => render_problems(frame, state),
+        CurrentScreen::Events => render_events(frame, state),
+        CurrentScreen::CrashLoops => render_crash_loops(frame, state),
+        CurrentScreen::Users => render_users(frame, state),
+        CurrentScreen::Sensors => render_sensors(frame, state),
+        CurrentScreen::Cgroups => render_cgroups(frame, state),
+        CurrentScreen::ResumeConfirm => render_resume_confirm(frame, state),
+        CurrentScreen::Limit => render_limit(frame, state),
+        CurrentScreen::Alerts => render_alerts(frame, state),
+        CurrentScreen::Audit => render_audit(frame, state),
         _ => {}
     }
 
@@ -97,7 +144,7 @@ pub fn ui_debug(frame: &mut Frame, state: &mut State) {
         filter_elapsed,
         proc_list_elapsed,
         popup_elapsed,
-        body_chunks[0],
+        banner_chunks[1],
     );
 }
 
@@ -105,13 +152,14 @@ pub fn ui_debug(frame: &mut Frame, state: &mut State) {
 ///
 /// # Assumptions
 /// We assume that the `chunks` parameter is a horizontal layout split into two parts
-fn render_title(frame: &mut Frame, chunk: Rect) {
+fn render_title(frame: &mut Frame, chunk: Rect, state: &State) {
     let title_chunks = Layout::default()
         .flex(Flex::Center)
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(5),
             Constraint::Max(4),
+            Constraint::Length(state.power.batteries.len() as u16),
             Constraint::Fill(1),
         ])
         .split(chunk);
@@ -241,7 +289,50 @@ fn render_title(frame: &mut Frame, chunk: Rect) {
 
     frame.render_widget(title, title_chunks[0]);
     frame.render_widget(welcome, title_chunks[1]);
-    frame.render_widget(gigachad_art, title_chunks[2]);
+    render_power(frame, title_chunks[2], state);
+    frame.render_widget(gigachad_art, title_chunks[3]);
+}
+
+/// one gauge line per battery, nothing at all on machines without one
+fn render_power(frame: &mut Frame, chunk: Rect, state: &State) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(state.power.batteries.iter().map(|_| Constraint::Length(1)))
+        .split(chunk);
+
+    for (battery, row) in state.power.batteries.iter().zip(rows.iter()) {
+        let capacity = battery.capacity.unwrap_or(0);
+        let color = if capacity < 20 {
+            Color::LightRed
+        } else if capacity < 50 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+
+        let mut label = format!("{} {}%", battery.name, capacity);
+        if state.power.ac_online == Some(true) {
+            label += " ac";
+        }
+        if let Some(status) = &battery.status {
+            label += &format!(" {}", status.to_lowercase());
+        }
+        if let Some(power) = battery.power.filter(|p| *p > 0.0) {
+            label += &format!(" {:.1}W", power);
+        }
+        if let Some(secs) = battery.time_to_empty {
+            label += &format!(" {} left", secs_to_str(secs));
+        } else if let Some(secs) = battery.time_to_full {
+            label += &format!(" {} to full", secs_to_str(secs));
+        }
+
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color).bg(Color::DarkGray))
+            .percent(capacity.into())
+            .label(label);
+
+        frame.render_widget(gauge, centered_rect(80, 100, *row));
+    }
 }
 
 /// renders the process list and handles state for drawing selector, sorting differently, etc.
@@ -261,32 +352,155 @@ fn render_proc_list(frame: &mut Frame, chunk: Rect, state: &mut State) {
             default_style.fg(Color::LightBlue),
         ))
         .title(Line::styled(
-            format!("{}", state.process_sort_strategy),
+            match state.process_sort_strategy {
+                ProcessSortStrategy::Memory => {
+                    format!("{} ({})", state.process_sort_strategy, state.memory_metric)
+                }
+                _ => format!("{}", state.process_sort_strategy),
+            },
             default_style.fg(state.process_sort_strategy.get_color()),
         ))
         .borders(Borders::LEFT | Borders::TOP)
         .padding(Padding::left(1));
 
-    let header_cells = ["pid", "name", "memory", "cpu usage", "user", "ppid"].map(Cell::new);
+    let process_block = match state.group_by {
+        GroupBy::None => process_block,
+        group_by => process_block.title(Line::styled(
+            format!("by {}", group_by),
+            default_style.fg(Color::LightMagenta),
+        )),
+    };
+
+    let process_block = match state.selected_row() {
+        Some(ProcRow::Group(idx)) => {
+            let group = &state.groups[idx];
+            process_block.title_bottom(Line::from(format!(
+                " {} procs, {} memory, {:.2}% cpu, {} read, {} written ",
+                group.members.len(),
+                bytes_to_str(group.memory),
+                group.cpu_usage,
+                bytes_to_str(group.disk_usage_read),
+                bytes_to_str(group.disk_usage_written),
+            )))
+        }
+        _ => process_block,
+    };
+
+    let memory_header = match state.memory_metric {
+        MemoryMetric::Rss => String::from("memory"),
+        metric => format!("mem ({})", metric),
+    };
+
+    let header_cells = [
+        "pid",
+        "name",
+        "state",
+        memory_header.as_str(),
+        "cpu usage",
+        "net rx",
+        "net tx",
+        "user",
+        "ppid",
+        "ns pid",
+        "cgroup",
+    ]
+    .map(Cell::new);
 
     let process_table_header = Row::new(header_cells)
         .style(default_style.fg(Color::Blue))
         .bold()
         .height(1);
 
-    let rows = state.processes.iter().map(|process| {
+    let grouped = state.group_by != GroupBy::None;
+    let rates_to_str =
+        |rate: Option<u64>| rate.map_or("n/a".to_string(), |r| bytes_to_str(r) + "/s");
+    let cpu_to_str = |usage: f32, limit: Option<f32>| match limit {
+        Some(limit) => format!("{:.2}% ≤{:.0}%", usage, limit),
+        None => format!("{:.2}%", usage),
+    };
+
+    let rows = state.rows.iter().map(|row| {
+        let process = match row {
+            ProcRow::Proc(idx) => &state.processes[*idx],
+            ProcRow::Group(idx) => {
+                let group = &state.groups[*idx];
+                let expanded = state.expanded_groups.contains(&group.key);
+
+                let row = [
+                    String::from(if expanded { "[-]" } else { "[+]" }),
+                    format!("{} ({})", group.key, group.members.len()),
+                    String::new(),
+                    bytes_to_str(group.memory),
+                    cpu_to_str(group.cpu_usage, group_limit(state, group)),
+                    rates_to_str(group.net_rx),
+                    rates_to_str(group.net_tx),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                ];
+
+                return Row::new(row.map(Cell::new))
+                    .style(default_style.bg(Color::DarkGray).bold())
+                    .height(1);
+            }
+        };
+
+        // processes chadtop paused stand out from ones stopped by something else
+        let run_state = match state.pause_tracker.get(process) {
+            Some(PauseKind::Stopped) => Cell::new("paused").style(
+                default_style
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Some(PauseKind::Frozen) => Cell::new("frozen").style(
+                default_style
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            None => Cell::new(process.state.to_string()).style(
+                default_style
+                    .fg(run_state_color(process.state))
+                    .add_modifier(Modifier::BOLD),
+            ),
+        };
+
+        // members of an expanded group are indented under it
+        let indent = if grouped { "  " } else { "" };
+
         let row = [
-            process.pid.to_string(),
-            process.name.as_deref().unwrap_or("n/a").to_string(),
-            bytes_to_str(process.memory),
-            format!("{:.2}%", process.cpu_usage),
+            format!("{}{}", indent, process.pid),
+            format!("{}{}", indent, process.name.as_deref().unwrap_or("n/a")),
+            process
+                .memory_by(state.memory_metric)
+                .map_or("n/a".to_string(), bytes_to_str),
+            cpu_to_str(
+                process.cpu_usage,
+                state.throttler.target_for(
+                    process.pid,
+                    process.cgroup.as_ref().and_then(|c| c.v2_path.as_deref()),
+                ),
+            ),
+            rates_to_str(process.net_rx),
+            rates_to_str(process.net_tx),
             process.user.as_deref().unwrap_or("n/a").to_string(),
             process
                 .ppid
                 .map_or("n/a".to_string(), |ppid| ppid.to_string()),
+            // only processes in a nested pid namespace have a pid of their own
+            process
+                .inner_pid()
+                .map_or(String::new(), |pid| pid.to_string()),
+            // the friendly owner when there is one, otherwise the raw path
+            process.cgroup.as_ref().map_or("n/a".to_string(), |c| {
+                c.owner.as_ref().map_or(c.path.clone(), |o| o.to_string())
+            }),
         ];
 
-        Row::new(row.map(Cell::new))
+        let mut cells: Vec<Cell> = row.map(Cell::new).into();
+        cells.insert(2, run_state);
+
+        Row::new(cells)
             .style(default_style.bg(Color::DarkGray))
             .height(1)
     });
@@ -296,10 +510,15 @@ fn render_proc_list(frame: &mut Frame, chunk: Rect, state: &mut State) {
         [
             Constraint::Length(7),
             Constraint::Length(30),
+            Constraint::Length(7),
             Constraint::Length(15),
-            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
             Constraint::Length(20),
             Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Fill(1),
         ],
     )
     .header(process_table_header)
@@ -315,6 +534,22 @@ fn render_proc_list(frame: &mut Frame, chunk: Rect, state: &mut State) {
     frame.render_stateful_widget(t, chunk, &mut state.processes_state);
 }
 
+/// the cpu limit of a group, either set on the group itself or on the cgroup its members share
+fn group_limit(state: &State, group: &ProcGroup) -> Option<f32> {
+    let by_label = state
+        .throttler
+        .target_of(&format!("{} {}", state.group_by, group.key));
+
+    by_label.or_else(|| {
+        if state.group_by != GroupBy::Cgroup {
+            return None;
+        }
+        let first = &state.processes[group.members[0]];
+        let path = first.cgroup.as_ref()?.v2_path.as_deref()?;
+        state.throttler.target_of(path)
+    })
+}
+
 /// renders the additional info about a process
 ///
 /// # Assumptions
@@ -336,6 +571,14 @@ fn render_proc_info_popup(frame: &mut Frame, state: &mut State) {
         }
     };
 
+    if state.proc_info_tab == ProcInfoTab::Maps {
+        let name = state.processes[proc_idx]
+            .name
+            .clone()
+            .unwrap_or(String::from("no proc name"));
+        return render_proc_maps(frame, state, name);
+    }
+
     let proc = &state.processes[proc_idx];
 
     let pad_len = 18;
@@ -400,7 +643,28 @@ fn render_proc_info_popup(frame: &mut Frame, state: &mut State) {
     let cmd_key = cmd_key + ": ";
     cmd.insert_str(0, &cmd_key);
 
-    let items: Vec<ListItem> = Vec::from([
+    let detail = proc.memory_detail.clone().unwrap_or_default();
+    let memory_items = [
+        ("rss", MemoryMetric::Rss),
+        ("pss", MemoryMetric::Pss),
+        ("uss", MemoryMetric::Uss),
+        ("shared", MemoryMetric::Shared),
+        ("swap", MemoryMetric::Swap),
+        ("vm peak", MemoryMetric::VmPeak),
+        ("vm hwm", MemoryMetric::VmHwm),
+    ]
+    .map(|(key, metric)| {
+        let mut key = String::from(key);
+        right_pad(&mut key, pad_len);
+        let value = match metric {
+            // fall back to sysinfo's number when smaps_rollup isn't available
+            MemoryMetric::Rss => Some(detail.rss.unwrap_or(proc.memory)),
+            _ => detail.get(metric),
+        };
+        ListItem::from(key + ": " + &value.map_or("n/a".to_string(), bytes_to_str))
+    });
+
+    let mut items: Vec<ListItem> = Vec::from([
         ListItem::from(pid),
         ListItem::from(start_time),
         ListItem::from(run_time),
@@ -412,17 +676,260 @@ fn render_proc_info_popup(frame: &mut Frame, state: &mut State) {
         ListItem::from(exe),
         ListItem::from(cmd),
     ]);
+    items.extend(memory_items);
+
+    let mut ns_pids_key = String::from("ns pids");
+    right_pad(&mut ns_pids_key, pad_len);
+    let ns_pids = match proc.ns_pids.as_slice() {
+        [] => String::from("n/a"),
+        pids => pids
+            .iter()
+            .map(|pid| pid.to_string())
+            .collect::<Vec<String>>()
+            .join(" -> "),
+    };
+    items.push(ListItem::from(ns_pids_key + ": " + &ns_pids));
+
+    // namespaces chadtop isn't in are highlighted since that's where containers show up
+    items.extend(NsKind::ALL.map(|kind| {
+        let mut key = format!("{} ns", kind);
+        right_pad(&mut key, pad_len);
+        let inode = proc.namespaces.get(kind);
+        let item = ListItem::from(key + ": " + &inode.map_or("n/a".to_string(), |i| i.to_string()));
+
+        if inode.is_some() && inode != state.own_namespaces.get(kind) {
+            item.style(Style::default().fg(Color::Yellow))
+        } else {
+            item
+        }
+    }));
+
+    // cgroup v1 and kernels without psi don't have per cgroup pressure
+    if let Some((path, pressure)) = &state.cgroup_pressure {
+        let mut cgroup_key = String::from("cgroup");
+        right_pad(&mut cgroup_key, pad_len);
+        items.push(ListItem::from(cgroup_key + ": " + path));
+
+        let mut psi_key = String::from("cgroup psi avg10");
+        right_pad(&mut psi_key, pad_len);
+        let values: Vec<String> = PsiResource::ALL
+            .iter()
+            .map(|resource| {
+                let avg10 = pressure
+                    .get(*resource)
+                    .map_or(String::from("n/a"), |p| format!("{:.2}%", p.some.avg10));
+                format!("{} {}", resource, avg10)
+            })
+            .collect();
+        items.push(ListItem::from(psi_key + ": " + &values.join(", ")));
+    }
 
     let area = proc_info_popup_area(70, frame.area());
 
-    let l = List::new(items).block(black_title_block(Title::from(
+    let l = List::new(items).block(black_title_block(Title::from(format!(
+        "{} [overview] / maps",
         proc.name.clone().unwrap_or(String::from("no proc name")),
-    )));
+    ))));
 
     frame.render_widget(Clear, area);
     frame.render_widget(l, area);
 }
 
+/// renders the memory maps tab of the process info popup, either every mapping or mappings
+/// grouped by their backing file
+fn render_proc_maps(frame: &mut Frame, state: &mut State, name: String) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let (header_cells, rows, widths): (Vec<&str>, Vec<Row>, Vec<Constraint>) = if state.maps_grouped
+    {
+        let rows = group_by_file(&state.maps)
+            .into_iter()
+            .map(|group| {
+                Row::new([
+                    group.name,
+                    group.count.to_string(),
+                    bytes_to_str(group.size),
+                    bytes_to_str(group.rss),
+                ])
+            })
+            .collect();
+
+        (
+            Vec::from(["file", "maps", "size", "rss"]),
+            rows,
+            Vec::from([
+                Constraint::Fill(1),
+                Constraint::Length(6),
+                Constraint::Length(11),
+                Constraint::Length(11),
+            ]),
+        )
+    } else {
+        let rows = state
+            .maps
+            .iter()
+            .map(|map| {
+                Row::new([
+                    format!("{:x}-{:x}", map.start, map.end),
+                    map.perms.clone(),
+                    bytes_to_str(map.size()),
+                    map.rss.map_or("n/a".to_string(), bytes_to_str),
+                    map.path.clone().unwrap_or(String::from("[anon]")),
+                ])
+            })
+            .collect();
+
+        (
+            Vec::from(["address", "perms", "size", "rss", "file"]),
+            rows,
+            Vec::from([
+                Constraint::Length(25),
+                Constraint::Length(5),
+                Constraint::Length(11),
+                Constraint::Length(11),
+                Constraint::Fill(1),
+            ]),
+        )
+    };
+
+    let total_size: u64 = state.maps.iter().map(|m| m.size()).sum();
+    let total_rss: u64 = state.maps.iter().filter_map(|m| m.rss).sum();
+
+    let header = Row::new(header_cells)
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    let t = Table::new(rows, widths)
+        .header(header)
+        .row_highlight_style(
+            default_style
+                .add_modifier(Modifier::REVERSED)
+                .fg(Color::Blue),
+        )
+        .highlight_symbol(Text::raw(" > "))
+        .highlight_spacing(HighlightSpacing::Always)
+        .block(
+            black_title_block(Title::from(format!("{} overview / [maps]", name))).title_bottom(
+                Line::from(match state.replay {
+                    Some(_) => String::from("maps aren't recorded"),
+                    None => format!(
+                        "{} mappings, {} mapped, {} resident",
+                        state.maps.len(),
+                        bytes_to_str(total_size),
+                        bytes_to_str(total_rss)
+                    ),
+                }),
+            ),
+        );
+
+    frame.render_stateful_widget(t, area, &mut state.maps_state);
+}
+
+/// renders warnings that need attention above the filter, uses the space debug mode puts its
+/// stats in
+fn render_banner(frame: &mut Frame, chunk: Rect, state: &State) {
+    let mut lines: Vec<Line> = Vec::new();
+
+    if let Some(replay) = &state.replay {
+        lines.push(Line::styled(
+            format!(
+                "replay {} utc, frame {}/{} at {}x{} [.] play [[/]] step [{{/}}] seek [-/+] speed",
+                replay.time().map_or(String::from("n/a"), format_utc),
+                replay.frame + 1,
+                replay.recording.len(),
+                replay.speed,
+                if replay.paused { ", paused" } else { "" }
+            ),
+            Style::default().fg(Color::LightMagenta).bold(),
+        ));
+    }
+
+    match &state.record_error {
+        Some(error) => lines.push(Line::styled(
+            format!("! recording stopped: {}", error),
+            Style::default().fg(Color::LightRed).bold(),
+        )),
+        None if state.recorder.is_some() => lines.push(Line::styled(
+            "recording",
+            Style::default().fg(Color::LightMagenta),
+        )),
+        None => {}
+    }
+
+    if !state.crash_loops.is_empty() {
+        let names: Vec<String> = state
+            .crash_loops
+            .iter()
+            .map(|l| format!("{} ({}x)", l.name.as_deref().unwrap_or("n/a"), l.exits))
+            .collect();
+
+        lines.push(Line::styled(
+            format!(
+                "! crash looping in the last {}s: {} [l]",
+                state.crash_loop_rule.window,
+                names.join(", ")
+            ),
+            Style::default().fg(Color::LightRed).bold(),
+        ));
+    }
+
+    let firing: Vec<String> = state
+        .alerts
+        .firing()
+        .iter()
+        .map(|a| format!("{} {}", a.rule, a.subject))
+        .collect();
+
+    if !firing.is_empty() {
+        lines.push(Line::styled(
+            format!("! alerts: {} [!]", firing.join(", ")),
+            Style::default().fg(Color::LightRed).bold(),
+        ));
+    }
+
+    let critical: Vec<String> = state
+        .sensors
+        .iter()
+        .filter(|s| s.level() == TempLevel::Critical)
+        .map(|s| format!("{} {:.0}°C", s.label, s.temperature.unwrap_or(0.0)))
+        .collect();
+
+    if !critical.is_empty() {
+        lines.push(Line::styled(
+            format!("! critical temperature: {} [t]", critical.join(", ")),
+            Style::default().fg(Color::LightRed).bold(),
+        ));
+    }
+
+    if !state.pause_tracker.is_empty() {
+        lines.push(Line::styled(
+            format!(
+                "paused by chadtop: {} processes, {} cgroups [R] to resume",
+                state.pause_tracker.pid_count(),
+                state.pause_tracker.cgroup_count()
+            ),
+            Style::default().fg(Color::LightCyan).bold(),
+        ));
+    }
+
+    if lines.is_empty() {
+        return;
+    }
+
+    let banner = Paragraph::new(Text::from(lines)).left_aligned().block(
+        Block::default()
+            .borders(Borders::LEFT)
+            .padding(Padding::left(1)),
+    );
+
+    frame.render_widget(banner, chunk);
+}
+
 fn render_filter(frame: &mut Frame, chunk: Rect, state: &mut State) {
     let filter_block = Block::default().borders(Borders::BOTTOM | Borders::LEFT);
 
@@ -448,18 +955,43 @@ fn render_sysinfo(frame: &mut Frame, state: &mut State) {
 
     let hsplit = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(7), Constraint::Fill(1)])
+        .constraints([Constraint::Length(9), Constraint::Fill(1)])
         .split(area);
+    let top_vsplit = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(hsplit[0]);
     let bottom_vsplit = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(hsplit[1]);
 
-    render_sysinfo_info(frame, hsplit[0], state);
+    render_sysinfo_info(frame, top_vsplit[0], state);
+
+    render_sysinfo_status(frame, top_vsplit[1], state);
+
+    // kernels without psi just get a taller cpu list
+    if state.pressure.is_available() {
+        let left_hsplit = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(8)])
+            .split(bottom_vsplit[0]);
+
+        render_sysinfo_cpu(frame, left_hsplit[0], state);
 
-    render_sysinfo_cpu(frame, bottom_vsplit[0], state);
+        render_sysinfo_pressure(frame, left_hsplit[1], state);
+    } else {
+        render_sysinfo_cpu(frame, bottom_vsplit[0], state);
+    }
+
+    let right_hsplit = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Fill(1)])
+        .split(bottom_vsplit[1]);
+
+    render_sysinfo_mem(frame, right_hsplit[0], state);
 
-    render_sysinfo_mem(frame, bottom_vsplit[1], state);
+    render_sysinfo_topology(frame, right_hsplit[1], state);
 }
 
 fn render_sysinfo_info(frame: &mut Frame, chunk: Rect, state: &mut State) {
@@ -503,11 +1035,33 @@ fn render_sysinfo_info(frame: &mut Frame, chunk: Rect, state: &mut State) {
     let physical_core_count_key = physical_core_count_key + ": ";
     physical_core_count.insert_str(0, &physical_core_count_key);
 
+    let mut cpu_brand = state
+        .cpu_details
+        .brand
+        .clone()
+        .unwrap_or(String::from("unknown"));
+    let mut cpu_brand_key = String::from("cpu");
+    right_pad(&mut cpu_brand_key, pad_len);
+    let cpu_brand_key = cpu_brand_key + ": ";
+    cpu_brand.insert_str(0, &cpu_brand_key);
+
+    let mut cpu_vendor = state
+        .cpu_details
+        .vendor
+        .clone()
+        .unwrap_or(String::from("unknown"));
+    let mut cpu_vendor_key = String::from("cpu vendor");
+    right_pad(&mut cpu_vendor_key, pad_len);
+    let cpu_vendor_key = cpu_vendor_key + ": ";
+    cpu_vendor.insert_str(0, &cpu_vendor_key);
+
     let items: Vec<ListItem> = Vec::from([
         ListItem::from(Text::raw(os).left_aligned()),
         ListItem::from(Text::raw(kernel).left_aligned()),
         ListItem::from(Text::raw(hostname).left_aligned()),
         ListItem::from(Text::raw(cpu_arch).left_aligned()),
+        ListItem::from(Text::raw(cpu_brand).left_aligned()),
+        ListItem::from(Text::raw(cpu_vendor).left_aligned()),
         ListItem::from(Text::raw(physical_core_count).left_aligned()),
     ]);
 
@@ -516,21 +1070,222 @@ fn render_sysinfo_info(frame: &mut Frame, chunk: Rect, state: &mut State) {
     frame.render_widget(l, chunk);
 }
 
+fn render_sysinfo_status(frame: &mut Frame, chunk: Rect, state: &mut State) {
+    let pad_len = 11;
+    let key = |name: &str| {
+        let mut key = String::from(name);
+        right_pad(&mut key, pad_len);
+        key + ": "
+    };
+
+    // the load is only meaningful next to how many cpus can share it
+    let cores = core_count(&state.cpus);
+    let load = Line::from(vec![
+        Span::raw(key("load")),
+        Span::styled(
+            format!("{:.2}", state.status.load_one),
+            Style::default().fg(load_color(state.status.load_one, cores)),
+        ),
+        Span::raw(" / "),
+        Span::styled(
+            format!("{:.2}", state.status.load_five),
+            Style::default().fg(load_color(state.status.load_five, cores)),
+        ),
+        Span::raw(" / "),
+        Span::styled(
+            format!("{:.2}", state.status.load_fifteen),
+            Style::default().fg(load_color(state.status.load_fifteen, cores)),
+        ),
+    ]);
+
+    let items: Vec<ListItem> = Vec::from([
+        ListItem::from(load),
+        ListItem::from(key("uptime") + &secs_to_str(state.status.uptime)),
+        ListItem::from(key("booted") + &format_utc(state.status.boot_time) + " utc"),
+        ListItem::from(key("processes") + &state.status.process_count.to_string()),
+        ListItem::from(
+            key("threads")
+                + &state
+                    .status
+                    .thread_count
+                    .map_or(String::from("n/a"), |t| t.to_string()),
+        ),
+    ]);
+
+    let l = List::new(items).block(black_title_block(Title::from("system status")));
+
+    frame.render_widget(l, chunk);
+}
+
+/// the colors the cpu time breakdown is drawn in, in the order the bar stacks them
+const CPU_TIME_COLORS: [(&str, Color); 7] = [
+    ("user", Color::Green),
+    ("nice", Color::Blue),
+    ("sys", Color::Red),
+    ("iowait", Color::Yellow),
+    ("irq", Color::Magenta),
+    ("sirq", Color::LightMagenta),
+    ("steal", Color::Cyan),
+];
+
 fn render_sysinfo_cpu(frame: &mut Frame, chunk: Rect, state: &mut State) {
+    // borders, "cpu10   : 100.00% ", " @ 4200 MHz" and the brackets around the bar
+    let bar_width = chunk.width.saturating_sub(2 + 18 + 12 + 2) as usize;
+
     let items: Vec<ListItem> = state
         .cpus
         .iter()
         .map(|cpu| {
             let mut cpu_name = cpu.name.clone();
             right_pad(&mut cpu_name, 8);
-            ListItem::from(Text::raw(format!("{}: {:.2}%", cpu_name, cpu.usage)).left_aligned())
+            let frequency = cpu
+                .frequency
+                .map_or(String::new(), |f| format!(" @ {} MHz", f));
+
+            let mut spans = vec![Span::raw(format!("{}: {:>6.2}% ", cpu_name, cpu.usage))];
+            if let Some(breakdown) = cpu.breakdown {
+                spans.push(Span::raw("["));
+                spans.extend(cpu_time_bar(breakdown, bar_width));
+                spans.push(Span::raw("]"));
+            }
+            spans.push(Span::raw(frequency));
+
+            ListItem::from(Line::from(spans))
         })
         .collect();
 
-    let l = List::new(items).block(black_title_block(Title::from("cpu")));
+    let legend: Vec<Span> = CPU_TIME_COLORS
+        .iter()
+        .map(|(name, color)| Span::styled(format!(" {} ", name), Style::default().fg(*color)))
+        .collect();
+
+    let l = List::new(items)
+        .block(black_title_block(Title::from("cpu")).title_bottom(Line::from(legend)));
     frame.render_widget(l, chunk);
 }
 
+/// a `width` wide bar with a colored segment per kind of busy time, idle is left blank
+///
+/// segment ends are rounded from the running total so the bar doesn't drift by a cell per segment
+fn cpu_time_bar(breakdown: CpuBreakdown, width: usize) -> Vec<Span<'static>> {
+    let busy = [
+        breakdown.user,
+        breakdown.nice,
+        breakdown.system,
+        breakdown.iowait,
+        breakdown.irq,
+        breakdown.softirq,
+        breakdown.steal,
+    ];
+
+    let mut spans = Vec::new();
+    let mut total = 0.0;
+    let mut drawn = 0;
+    for (pct, (_, color)) in busy.iter().zip(CPU_TIME_COLORS.iter()) {
+        total += pct;
+        let end = ((total / 100.0 * width as f32).round() as usize).min(width);
+        if end > drawn {
+            spans.push(Span::styled(
+                "|".repeat(end - drawn),
+                Style::default().fg(*color),
+            ));
+            drawn = end;
+        }
+    }
+    spans.push(Span::raw(" ".repeat(width - drawn)));
+
+    spans
+}
+
+/// some avg10/avg60/avg300 of every resource with a sparkline of avg10 under each
+fn render_sysinfo_pressure(frame: &mut Frame, chunk: Rect, state: &mut State) {
+    let block = black_title_block(Title::from("pressure (some avg10 / avg60 / avg300)"));
+    let inner = block.inner(chunk);
+    frame.render_widget(block, chunk);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2); 3])
+        .split(inner);
+
+    for (resource, row) in PsiResource::ALL.iter().zip(rows.iter()) {
+        let row_split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1)])
+            .split(*row);
+
+        let mut name = resource.to_string();
+        right_pad(&mut name, 7);
+        let text = match state.pressure.get(*resource) {
+            Some(p) => Line::from(vec![
+                Span::raw(name + ": "),
+                Span::styled(
+                    format!(
+                        "{:.2}% / {:.2}% / {:.2}%",
+                        p.some.avg10, p.some.avg60, p.some.avg300
+                    ),
+                    Style::default().fg(pressure_color(p.some.avg10)),
+                ),
+            ]),
+            None => Line::from(name + ": n/a"),
+        };
+        frame.render_widget(Paragraph::new(text), row_split[0]);
+
+        // only the newest samples that fit, scaled to at least 10% so idle noise stays flat
+        let history = state.psi_history.get(*resource);
+        let data: Vec<u64> = history
+            .iter()
+            .skip(history.len().saturating_sub(row_split[1].width as usize))
+            .copied()
+            .collect();
+        let max = data.iter().copied().max().unwrap_or(0).max(1000);
+        let sparkline = Sparkline::default()
+            .data(&data)
+            .max(max)
+            .style(Style::default().fg(Color::LightBlue));
+        frame.render_widget(sparkline, row_split[1]);
+    }
+}
+
+/// which package and core every logical cpu belongs to and which cpus share a core through smt
+fn render_sysinfo_topology(frame: &mut Frame, chunk: Rect, state: &mut State) {
+    let default_style = Style::default();
+    let or_na = |n: Option<u64>| n.map_or(String::from("n/a"), |n| n.to_string());
+
+    let header = Row::new(["cpu", "pkg", "core", "smt siblings", "min MHz", "max MHz"])
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    let rows = state.cpu_details.topology.iter().map(|t| {
+        Row::new([
+            format!("cpu{}", t.cpu),
+            or_na(t.package.map(u64::from)),
+            or_na(t.core.map(u64::from)),
+            t.siblings.clone().unwrap_or(String::from("n/a")),
+            or_na(t.min_frequency),
+            or_na(t.max_frequency),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Fill(1),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(black_title_block(Title::from("topology")));
+
+    frame.render_widget(t, chunk);
+}
+
 fn render_sysinfo_mem(frame: &mut Frame, chunk: Rect, state: &mut State) {
     let pad_len = 11;
 
@@ -612,11 +1367,31 @@ fn render_help(frame: &mut Frame) {
 [g] first process
 [G] last process
 [s] change (s)ort (s)trategy {i.e. cpu usage, name, memory, etc.}
-[K] (K)ill process
+[m] change (m)emory metric {rss, pss, uss, shared, swap, etc.}
+[a] (a)ggregate by name, exe, user, parent or cgroup
+[Enter/Space] expand or collapse the selected group
+[K] (K)ill process, or every process in a group
+[P] (P)ause process or group, cgroup groups are frozen
+[R] (R)esume process or group
+[L] (L)imit cpu of process or group, cgroup groups use cpu.max
 [i] system (i)nfo
 [d] currently selected process info
 [h] toggle (h)elp
 [f] toggle (f)ilter
+[p] sockets and (p)orts
+[z] (z)ombies, orphans and stuck processes
+[e] process spawn and exit (e)vents
+[l] crash (l)ooping processes
+[u] per (u)ser summary
+[t] (t)emperature sensors
+[c] (c)group tree with limits
+[n] filter to the selected process' (n)amespace, again for the next kind
+[!] firing alerts and alert history
+[H] (H)istory of kills and other actions taken on processes
+[.] in a replay, play or pause
+[[/]] in a replay, step a frame back or forward
+[{/}] in a replay, seek a minute back or forward
+[-/+] in a replay, slower or faster
 [Backspace] clear filter
 ",
     ))
@@ -629,6 +1404,15 @@ fn render_help(frame: &mut Frame) {
 [Esc] return to main mode
 [Enter] return to main mode
 all other keys filter processes
+terms are space separated, all must match
+port:<n> processes holding local port n, not during a replay
+state:<s> run, sleep, disk, zombie, stop, trace
+user:<name> processes owned by that user, n/a for unknown owners
+unit:<unit> processes in a systemd unit or slice
+container:<id> processes in a container or pod
+cgroup:<path> processes in a cgroup or below it
+ns:<kind>:<inode> processes in that pid, net, mnt, etc. namespace
+nspid:<n> processes with pid n inside their pid namespace
 ",
     ))
     .block(black_title_block(Title::from("filter mode keybinds")));
@@ -638,7 +1422,28 @@ all other keys filter processes
     let popup_keybinds = Paragraph::new(Text::raw(
         "[i] return to main mode from system (i)nfo
 [d] return to main mode from selected process info
+[Tab] switch process info between overview and memory maps
+[v] in memory maps, toggle grouping by file
 [h] return to main mode from (h)elp menu
+[p] return to main mode from (p)orts
+[Enter] in ports, jump to owning process
+[z] return to main mode from problems
+[Enter] in problems, jump to process (parent for zombies)
+[+/-] in problems, change disk sleep threshold
+[e] return to main mode from (e)vents
+[l] return to main mode from crash (l)oops
+[Enter] in crash loops, filter by the looping name
+[+/-] in crash loops, change how many exits make a loop
+[</>] in crash loops, change the detection window
+[u] return to main mode from (u)sers
+[t] return to main mode from (t)emperature sensors
+[Enter] in users, filter by the selected user
+[c] return to main mode from (c)groups
+[Enter] in cgroups, filter by the selected group
+[P/R] in cgroups, freeze or thaw the selected group
+[!] return to main mode from alerts
+[Enter] in alerts, jump to the process of the selected event
+[H] return to main mode from action (H)istory
 [Esc] return to main mode from any popup
 ",
     ))
@@ -648,6 +1453,31 @@ all other keys filter processes
 }
 
 fn render_killconfirm(frame: &mut Frame, state: &mut State) {
+    if let Some(key) = &state.current_group_watch {
+        let pids = state.watched_pids();
+        if pids.is_empty() {
+            // the group is gone so there's nothing left to kill
+            state.current_group_watch = None;
+            state.current_screen = CurrentScreen::Main;
+            return;
+        }
+
+        let killconfirm_text = Paragraph::new(Text::raw(format!(
+            "Are you sure you want to kill all {} processes in {} {}\npress [y]es / [n]o",
+            pids.len(),
+            state.group_by,
+            key,
+        )))
+        .centered()
+        .block(black_title_block(Title::from("kill confirm")));
+
+        let area = kill_confirm_popup_area(frame.area());
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(killconfirm_text, area);
+        return;
+    }
+
     let proc_idx = state
         .processes
         .iter()
@@ -680,90 +1510,936 @@ fn render_killconfirm(frame: &mut Frame, state: &mut State) {
     frame.render_widget(killconfirm_text, area);
 }
 
-fn render_debug(
-    frame: &mut Frame,
-    state: &State,
-    title: Duration,
-    filter: Duration,
-    proc_list: Duration,
-    popup: Duration,
-    chunk: Rect,
-) {
-    let layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(chunk);
+fn render_ports(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
 
-    let items: Vec<ListItem> = Vec::from([
-        ListItem::from(format!(
-            "sys collect: {:?}",
-            state.debug_stats_sys.unwrap_or(Instant::now().elapsed())
-        )),
-        ListItem::from(format!(
-            "state collect: {:?}",
-            state.debug_stats_state.unwrap_or(Instant::now().elapsed())
-        )),
-        ListItem::from(format!(
-            "ram collect: {:?}",
-            state.debug_stats_ram.unwrap_or(Instant::now().elapsed())
-        )),
-        ListItem::from(format!(
-            "cpu collect: {:?}",
-            state.debug_stats_cpu.unwrap_or(Instant::now().elapsed())
-        )),
-    ]);
+    frame.render_widget(Clear, area);
 
-    let l = List::new(items);
-    frame.render_widget(l, layout[0]);
+    let default_style = Style::default();
 
-    let items: Vec<ListItem> = Vec::from([
-        ListItem::from(format!("title render: {:?}", title)),
-        ListItem::from(format!("filter render: {:?}", filter)),
-        ListItem::from(format!("proc list render: {:?}", proc_list)),
-        ListItem::from(format!("popup render: {:?}", popup)),
-    ]);
+    let header_cells = ["proto", "local", "remote", "state", "pid", "process"].map(Cell::new);
 
-    let l = List::new(items);
-    frame.render_widget(l, layout[1]);
-}
+    let header = Row::new(header_cells)
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    // Cut the given rectangle into three vertical pieces
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
+    let rows = state.sockets.iter().map(|socket| {
+        let row = [
+            socket.protocol.to_string(),
+            socket.local.clone(),
+            socket.remote.clone().unwrap_or(String::from("*")),
+            socket.state.clone(),
+            socket.pid.map_or("n/a".to_string(), |pid| pid.to_string()),
+            socket.process_name.clone().unwrap_or(String::from("n/a")),
+        ];
 
-    // Then cut the middle vertical piece into three width-wise pieces
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1] // Return the middle chunk
-}
+        let state_color = match socket.state.as_str() {
+            "listen" => Color::Green,
+            "established" | "estab" | "connected" => Color::LightBlue,
+            _ => Color::White,
+        };
 
-/// helper function, similar to `centered_rect` from ratatui json editor tutorial, but has a
-/// constant y value since we need our signal_menu and other stuff rendered there to take a
-/// constant amount of space on the y axis
-fn proc_info_popup_area(percent_x: u16, r: Rect) -> Rect {
-    // Cut the given rectangle into three vertical pieces
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
+        Row::new(row.map(Cell::new))
+            .style(default_style.fg(state_color))
+            .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Fill(2),
             Constraint::Fill(1),
-            Constraint::Length(13),
+            Constraint::Length(11),
+            Constraint::Length(7),
             Constraint::Fill(1),
-        ])
-        .split(r);
-
-    // Then cut the middle vertical piece into three width-wise pieces
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!(
+            "ports ({} sockets)",
+            state.sockets.len()
+        )))
+        .title_bottom(Line::from(if state.replay.is_some() {
+            "sockets aren't recorded"
+        } else {
+            "[Enter] to jump to the owning process"
+        })),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.sockets_state);
+}
+
+fn render_problems(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let header = Row::new(["problem", "pid", "name", "detail"].map(Cell::new))
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    let rows = state.problems.iter().map(|problem| {
+        let (kind, color, name, detail) = match problem {
+            Problem::Zombie {
+                name,
+                ppid,
+                parent_name,
+                ..
+            } => (
+                "zombie",
+                Color::LightRed,
+                name,
+                format!(
+                    "not reaped by {} {}",
+                    ppid.map_or("n/a".to_string(), |p| p.to_string()),
+                    parent_name.as_deref().unwrap_or("")
+                ),
+            ),
+            Problem::Reparented {
+                name,
+                original_ppid,
+                ppid,
+                parent_name,
+                ..
+            } => (
+                "orphan",
+                Color::Yellow,
+                name,
+                format!(
+                    "parent {} exited, adopted by {} {}",
+                    original_ppid,
+                    ppid.map_or("n/a".to_string(), |p| p.to_string()),
+                    parent_name.as_deref().unwrap_or("")
+                ),
+            ),
+            Problem::StuckDiskSleep { name, since, .. } => (
+                "disk sleep",
+                Color::LightMagenta,
+                name,
+                format!("uninterruptible for {}s", since.as_secs()),
+            ),
+        };
+
+        Row::new([
+            Cell::new(kind).style(default_style.fg(color).add_modifier(Modifier::BOLD)),
+            Cell::new(problem.pid().to_string()),
+            Cell::new(name.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(detail),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(20),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!("problems ({})", state.problems.len())))
+            .title_bottom(Line::from(format!(
+                "disk sleep threshold {}s [+/-]",
+                state.disk_sleep_threshold.as_secs()
+            ))),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.problems_state);
+}
+
+fn render_events(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let header = Row::new(
+        [
+            "time (utc)",
+            "event",
+            "pid",
+            "name",
+            "parent",
+            "lifetime",
+            "cmd",
+        ]
+        .map(Cell::new),
+    )
+    .style(default_style.fg(Color::Blue))
+    .bold()
+    .height(1);
+
+    // newest first
+    let rows = state.event_log.events.iter().rev().map(|event| {
+        let (kind, color) = match event.kind {
+            EventKind::Spawn => ("spawn", Color::LightGreen),
+            EventKind::Exit => ("exit", Color::LightRed),
+        };
+
+        Row::new([
+            Cell::new(format_utc(event.time)),
+            Cell::new(kind).style(default_style.fg(color).add_modifier(Modifier::BOLD)),
+            Cell::new(event.pid.to_string()),
+            Cell::new(event.name.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(format!(
+                "{} {}",
+                event
+                    .ppid
+                    .map_or("n/a".to_string(), |ppid| ppid.to_string()),
+                event.parent_name.as_deref().unwrap_or("")
+            )),
+            Cell::new(event.lifetime.map_or(String::new(), secs_to_str)),
+            Cell::new(event.cmd.clone().unwrap_or_default()),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(5),
+            Constraint::Length(7),
+            Constraint::Length(16),
+            Constraint::Length(20),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(black_title_block(Title::from(format!(
+        "events ({})",
+        state.event_log.events.len()
+    ))));
+
+    frame.render_stateful_widget(t, area, &mut state.events_state);
+}
+
+fn render_alerts(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+    let firing = state.alerts.firing();
+
+    let hsplit = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(firing.len().max(1) as u16 + 3),
+            Constraint::Fill(1),
+        ])
+        .split(area);
+
+    let alert_value = |value: f64, unit: Unit| match unit {
+        Unit::Percent => format!("{:.1}%", value),
+        Unit::Bytes => bytes_to_str(value as u64),
+    };
+
+    let header = Row::new(["since (utc)", "rule", "subject", "value"].map(Cell::new))
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    let rows = firing.iter().map(|alert| {
+        Row::new([
+            Cell::new(format_utc(alert.since)),
+            Cell::new(alert.rule.clone()).style(
+                default_style
+                    .fg(Color::LightRed)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Cell::new(alert.subject.clone()),
+            Cell::new(alert_value(alert.value, alert.unit)),
+        ])
+        .height(1)
+    });
+
+    let source = match (&state.alerts.path, state.alerts.errors.first()) {
+        (_, Some(error)) => Line::styled(
+            format!("{} ({} errors)", error, state.alerts.errors.len()),
+            default_style.fg(Color::LightRed),
+        ),
+        (Some(path), None) => Line::from(format!(
+            "{} rules from {}",
+            state.alerts.rules.len(),
+            path.display()
+        )),
+        (None, None) => Line::from("no rules, see --alerts"),
+    };
+
+    let actions = state
+        .audit_log
+        .entries
+        .iter()
+        .filter(|e| e.source.starts_with("watchdog"))
+        .count();
+    let watchdog = if state.alerts.watchdog.is_dry_run() {
+        Line::styled(
+            format!("watchdog dry run, {} actions", actions),
+            default_style.fg(Color::Yellow),
+        )
+    } else {
+        Line::styled(
+            format!("watchdog armed, {} actions", actions),
+            default_style.fg(Color::LightRed).bold(),
+        )
+    };
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(20),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(
+        black_title_block(Title::from(format!("firing alerts ({})", firing.len())))
+            .title_bottom(source)
+            .title_bottom(watchdog.right_aligned()),
+    );
+
+    frame.render_widget(t, hsplit[0]);
+
+    let header = Row::new(["time (utc)", "alert", "rule", "subject", "value"].map(Cell::new))
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    // newest first
+    let rows = state.alerts.history.iter().rev().map(|event| {
+        let (kind, color) = match event.state {
+            AlertState::Fired => ("fired", Color::LightRed),
+            AlertState::Cleared => ("cleared", Color::LightGreen),
+        };
+
+        Row::new([
+            Cell::new(format_utc(event.time)),
+            Cell::new(kind).style(default_style.fg(color).add_modifier(Modifier::BOLD)),
+            Cell::new(event.rule.clone()),
+            Cell::new(event.subject.clone()),
+            Cell::new(alert_value(event.value, event.unit)),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(7),
+            Constraint::Length(20),
+            Constraint::Fill(1),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(black_title_block(Title::from(format!(
+        "alert history ({})",
+        state.alerts.history.len()
+    ))));
+
+    frame.render_stateful_widget(t, hsplit[1], &mut state.alert_history_state);
+}
+
+fn render_audit(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let header = Row::new(
+        [
+            "time (utc)",
+            "by",
+            "source",
+            "action",
+            "result",
+            "pid",
+            "name",
+            "user",
+            "cmd",
+        ]
+        .map(Cell::new),
+    )
+    .style(default_style.fg(Color::Blue))
+    .bold()
+    .height(1);
+
+    // newest first
+    let rows = state.audit_log.entries.iter().rev().map(|entry| {
+        let color = match entry.result.as_str() {
+            "ok" => Color::LightGreen,
+            "dry run" => Color::Yellow,
+            _ => Color::LightRed,
+        };
+
+        Row::new([
+            Cell::new(format_utc(entry.time)),
+            Cell::new(entry.actor.clone()),
+            Cell::new(entry.source.clone()),
+            Cell::new(entry.action.clone()),
+            Cell::new(entry.result.clone()).style(default_style.fg(color)),
+            Cell::new(entry.pid.to_string()),
+            Cell::new(entry.name.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(entry.user.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(entry.cmd.clone().unwrap_or_default()),
+        ])
+        .height(1)
+    });
+
+    let file = match (&state.audit_log.error, &state.audit_log.path) {
+        (Some(error), _) => Line::styled(error.clone(), default_style.fg(Color::LightRed)),
+        (None, Some(path)) => Line::from(format!("logged to {}", path.display())),
+        (None, None) => Line::from("not logged to a file, see --audit-log"),
+    };
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(7),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!(
+            "action history ({})",
+            state.audit_log.entries.len()
+        )))
+        .title_bottom(file),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.audit_state);
+}
+
+fn render_crash_loops(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let header =
+        Row::new(["name", "exits", "last exit (utc)", "last pid", "exe", "cmd"].map(Cell::new))
+            .style(default_style.fg(Color::Blue))
+            .bold()
+            .height(1);
+
+    let rows = state.crash_loops.iter().map(|crash_loop| {
+        Row::new([
+            Cell::new(crash_loop.name.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(crash_loop.exits.to_string()).style(
+                default_style
+                    .fg(Color::LightRed)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Cell::new(format_utc(crash_loop.last_exit)),
+            Cell::new(crash_loop.last_pid.to_string()),
+            Cell::new(crash_loop.exe.clone().unwrap_or(String::from("n/a"))),
+            Cell::new(crash_loop.cmd.clone().unwrap_or(String::from("n/a"))),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(6),
+            Constraint::Length(19),
+            Constraint::Length(8),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!(
+            "crash loops ({})",
+            state.crash_loops.len()
+        )))
+        .title_bottom(Line::from(format!(
+            "more than {} exits [+/-] of the same exe and cmd in {}s [</>]",
+            state.crash_loop_rule.max_exits, state.crash_loop_rule.window
+        ))),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.crash_loops_state);
+}
+
+fn render_sensors(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+    let or_na = |t: Option<f32>| t.map_or(String::from("n/a"), |t| format!("{:.1}°C", t));
+
+    let header = Row::new(["sensor", "current", "max", "critical", "history"].map(Cell::new))
+        .style(default_style.fg(Color::Blue))
+        .bold()
+        .height(1);
+
+    // borders, highlight symbol, the other columns and the spacing between them
+    let history_width = area.width.saturating_sub(2 + 3 + 30 + 10 * 3 + 4) as usize;
+
+    let rows = state.sensors.iter().map(|sensor| {
+        let color = match sensor.level() {
+            TempLevel::Normal => Color::Green,
+            TempLevel::Hot => Color::Yellow,
+            TempLevel::Critical => Color::LightRed,
+        };
+
+        let history = state
+            .sensor_history
+            .get(sensor.id)
+            .map(|h| {
+                let values: Vec<f32> = h.iter().copied().collect();
+                text_sparkline(&values, history_width)
+            })
+            .unwrap_or_default();
+
+        Row::new([
+            Cell::new(sensor.label.clone()),
+            Cell::new(or_na(sensor.temperature))
+                .style(default_style.fg(color).add_modifier(Modifier::BOLD)),
+            Cell::new(or_na(sensor.max)),
+            Cell::new(or_na(sensor.critical)),
+            Cell::new(history).style(default_style.fg(color)),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Length(30),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!("sensors ({})", state.sensors.len()))).title_bottom(
+            Line::from(if state.replay.is_some() {
+                "sensors aren't recorded"
+            } else if state.sensors.is_empty() {
+                "no temperature sensors found"
+            } else {
+                "yellow within 10°C of critical, red at or above critical"
+            }),
+        ),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.sensors_state);
+}
+
+fn render_limit(frame: &mut Frame, state: &mut State) {
+    let text = Paragraph::new(Text::from(vec![
+        Line::from(format!(
+            "limit cpu of {} to {}%",
+            state.limit_label.as_deref().unwrap_or("n/a"),
+            state.limit_input
+        ))
+        .bold(),
+        Line::from("percent of one cpu, empty or 0 removes the limit"),
+        Line::from("[Enter] apply / [Esc] cancel"),
+    ]))
+    .centered()
+    .block(black_title_block(Title::from("cpu limit")));
+
+    let area = kill_confirm_popup_area(frame.area());
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(text, area);
+}
+
+fn render_resume_confirm(frame: &mut Frame, state: &mut State) {
+    let text = Paragraph::new(Text::raw(format!(
+        "chadtop paused {} processes and froze {} cgroups\nresume them before quitting? [y]es / [n]o",
+        state.pause_tracker.pid_count(),
+        state.pause_tracker.cgroup_count(),
+    )))
+    .centered()
+    .block(black_title_block(Title::from("resume confirm")));
+
+    let area = kill_confirm_popup_area(frame.area());
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(text, area);
+}
+
+fn render_cgroups(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+    let or_na = |v: Option<u64>, f: fn(u64) -> String| v.map_or(String::from("n/a"), f);
+
+    let header = Row::new(
+        [
+            "group",
+            "cpu / limit",
+            "memory / limit",
+            "pids / limit",
+            "io read",
+            "io write",
+            "throttled",
+        ]
+        .map(Cell::new),
+    )
+    .style(default_style.fg(Color::Blue))
+    .bold()
+    .height(1);
+
+    let rows = state.cgroup_tree.nodes.iter().map(|node| {
+        let cpu_limit = node.cpu_limit();
+        let cpu = match (node.cpu_usage, cpu_limit) {
+            (Some(usage), Some(limit)) => format!("{:.1}% / {:.2}", usage, limit),
+            (Some(usage), None) => format!("{:.1}%", usage),
+            (None, Some(limit)) => format!("n/a / {:.2}", limit),
+            (None, None) => String::from("n/a"),
+        };
+        let cpu_fraction = node
+            .cpu_usage
+            .zip(cpu_limit)
+            .map(|(usage, limit)| usage / 100.0 / limit.max(f32::EPSILON));
+
+        let memory = match (node.memory_current, node.memory_max) {
+            (Some(current), Some(max)) => format!(
+                "{} / {} {:.0}%",
+                bytes_to_str(current),
+                bytes_to_str(max),
+                current as f64 / max.max(1) as f64 * 100.0
+            ),
+            (current, _) => or_na(current, bytes_to_str),
+        };
+        let memory_fraction = node
+            .memory_current
+            .zip(node.memory_max)
+            .map(|(current, max)| current as f32 / max.max(1) as f32);
+
+        let pids = match (node.pids_current, node.pids_max) {
+            (Some(current), Some(max)) => format!("{} / {}", current, max),
+            (current, _) => or_na(current, |c| c.to_string()),
+        };
+        let pids_fraction = node
+            .pids_current
+            .zip(node.pids_max)
+            .map(|(current, max)| current as f32 / max.max(1) as f32);
+
+        let throttled = match (node.nr_throttled, node.throttled_usec) {
+            (Some(count), Some(usec)) => format!("{} / {:.1}s", count, usec as f64 / 1e6),
+            (count, _) => or_na(count, |c| c.to_string()),
+        };
+        let throttled_color = match node.nr_throttled {
+            Some(count) if count > 0 => Color::Yellow,
+            _ => Color::Reset,
+        };
+
+        let name = Cell::new(format!(
+            "{}{}{}",
+            "  ".repeat(node.depth),
+            node.name(),
+            if node.frozen == Some(true) {
+                " (frozen)"
+            } else {
+                ""
+            }
+        ));
+        let name = if node.frozen == Some(true) {
+            name.style(default_style.fg(Color::LightCyan))
+        } else {
+            name
+        };
+
+        Row::new([
+            name,
+            Cell::new(cpu).style(default_style.fg(limit_color(cpu_fraction))),
+            Cell::new(memory).style(default_style.fg(limit_color(memory_fraction))),
+            Cell::new(pids).style(default_style.fg(limit_color(pids_fraction))),
+            Cell::new(or_na(node.io_read_bytes, bytes_to_str)),
+            Cell::new(or_na(node.io_written_bytes, bytes_to_str)),
+            Cell::new(throttled).style(default_style.fg(throttled_color)),
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(16),
+            Constraint::Length(26),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(14),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!(
+            "cgroups ({})",
+            state.cgroup_tree.nodes.len()
+        )))
+        .title_bottom(Line::from(if state.replay.is_some() {
+            "cgroups aren't recorded"
+        } else if state.cgroup_tree.nodes.is_empty() {
+            "no cgroup v2 hierarchy found"
+        } else {
+            "cpu limit is in cpus, [Enter] to filter by group, [P]ause / [R]esume to freeze"
+        })),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.cgroup_tree_state);
+}
+
+fn render_users(frame: &mut Frame, state: &mut State) {
+    let area = responsive_area(frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let default_style = Style::default();
+
+    let header = Row::new(
+        [
+            "user",
+            "procs",
+            "cpu usage",
+            "memory",
+            "disk read",
+            "disk write",
+            "sessions",
+        ]
+        .map(Cell::new),
+    )
+    .style(default_style.fg(Color::Blue))
+    .bold()
+    .height(1);
+
+    let rows = state.user_summaries.iter().map(|summary| {
+        let sessions = match &state.sessions {
+            Some(sessions) => sessions.get(&summary.key).copied().unwrap_or(0).to_string(),
+            None => String::from("n/a"),
+        };
+
+        Row::new([
+            summary.key.clone(),
+            summary.members.len().to_string(),
+            format!("{:.2}%", summary.cpu_usage),
+            bytes_to_str(summary.memory),
+            bytes_to_str(summary.disk_usage_read),
+            bytes_to_str(summary.disk_usage_written),
+            sessions,
+        ])
+        .height(1)
+    });
+
+    let t = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(
+        default_style
+            .add_modifier(Modifier::REVERSED)
+            .fg(Color::Blue),
+    )
+    .highlight_symbol(Text::raw(" > "))
+    .highlight_spacing(HighlightSpacing::Always)
+    .block(
+        black_title_block(Title::from(format!(
+            "users ({})",
+            state.user_summaries.len()
+        )))
+        .title_bottom(Line::from(format!(
+            "memory is {}, [Enter] to filter by user{}",
+            state.memory_metric,
+            if state.replay.is_some() {
+                ", sessions aren't recorded"
+            } else {
+                ""
+            }
+        ))),
+    );
+
+    frame.render_stateful_widget(t, area, &mut state.user_summaries_state);
+}
+
+fn render_debug(
+    frame: &mut Frame,
+    state: &State,
+    title: Duration,
+    filter: Duration,
+    proc_list: Duration,
+    popup: Duration,
+    chunk: Rect,
+) {
+    let layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunk);
+
+    let items: Vec<ListItem> = Vec::from([
+        ListItem::from(format!(
+            "sys collect: {:?}",
+            state.debug_stats_sys.unwrap_or(Instant::now().elapsed())
+        )),
+        ListItem::from(format!(
+            "state collect: {:?}",
+            state.debug_stats_state.unwrap_or(Instant::now().elapsed())
+        )),
+        ListItem::from(format!(
+            "ram collect: {:?}",
+            state.debug_stats_ram.unwrap_or(Instant::now().elapsed())
+        )),
+        ListItem::from(format!(
+            "cpu collect: {:?}",
+            state.debug_stats_cpu.unwrap_or(Instant::now().elapsed())
+        )),
+    ]);
+
+    let l = List::new(items);
+    frame.render_widget(l, layout[0]);
+
+    let items: Vec<ListItem> = Vec::from([
+        ListItem::from(format!("title render: {:?}", title)),
+        ListItem::from(format!("filter render: {:?}", filter)),
+        ListItem::from(format!("proc list render: {:?}", proc_list)),
+        ListItem::from(format!("popup render: {:?}", popup)),
+    ]);
+
+    let l = List::new(items);
+    frame.render_widget(l, layout[1]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    // Cut the given rectangle into three vertical pieces
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    // Then cut the middle vertical piece into three width-wise pieces
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1] // Return the middle chunk
+}
+
+/// helper function, similar to `centered_rect` from ratatui json editor tutorial, but has a
+/// constant y value since we need our signal_menu and other stuff rendered there to take a
+/// constant amount of space on the y axis
+fn proc_info_popup_area(percent_x: u16, r: Rect) -> Rect {
+    // Cut the given rectangle into three vertical pieces
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Fill(1),
+            Constraint::Length(29),
+            Constraint::Fill(1),
+        ])
+        .split(r);
+
+    // Then cut the middle vertical piece into three width-wise pieces
     Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -799,6 +2475,18 @@ fn kill_confirm_popup_area(r: Rect) -> Rect {
         .split(popup_layout[1])[1] // Return the middle chunk
 }
 
+/// badge color of a process' run state in the process table
+fn run_state_color(run_state: RunState) -> Color {
+    match run_state {
+        RunState::Running => Color::LightGreen,
+        RunState::Sleeping | RunState::Idle => Color::Gray,
+        RunState::DiskSleep => Color::LightMagenta,
+        RunState::Zombie | RunState::Dead => Color::LightRed,
+        RunState::Stopped | RunState::Traced => Color::Yellow,
+        RunState::Unknown => Color::White,
+    }
+}
+
 /// helper function to create black title block
 fn black_title_block(title: Title) -> Block {
     Block::default()
@@ -844,6 +2532,82 @@ fn bytes_to_str(bytes: u64) -> String {
     format!("{:.2} {}", gb, "gb")
 }
 
+/// converts a number of seconds to a short human readable duration
+///
+/// # Examples
+/// ```rust
+/// secs_to_str(42); // "42s"
+/// secs_to_str(3723); // "1h 02m"
+/// ```
+fn secs_to_str(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else if secs < 86400 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else {
+        format!("{}d {:02}h", secs / 86400, (secs % 86400) / 3600)
+    }
+}
+
+/// draws the last `width` values as a one line sparkline scaled between their min and max, so
+/// it fits in a table cell
+fn text_sparkline(values: &[f32], width: usize) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let values = &values[values.len().saturating_sub(width)..];
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let range = (max - min).max(1.0);
+
+    values
+        .iter()
+        .map(|v| BARS[(((v - min) / range) * (BARS.len() - 1) as f32).round() as usize])
+        .collect()
+}
+
+/// green when tasks barely wait, yellow when they're noticeably stalled and red when they're
+/// stalled a lot of the time
+fn pressure_color(avg: f32) -> Color {
+    if avg < 1.0 {
+        Color::Green
+    } else if avg < 10.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+/// how close a cgroup is to one of its limits, green with room to spare, yellow past 75% and red
+/// past 90%. Groups without a limit keep the default color
+fn limit_color(fraction: Option<f32>) -> Color {
+    match fraction {
+        Some(f) if f >= 0.9 => Color::Red,
+        Some(f) if f >= 0.75 => Color::Yellow,
+        Some(_) => Color::Green,
+        None => Color::Reset,
+    }
+}
+
+/// how many cpus there are, leaving out the `overall` entry `read_cpus` puts first
+fn core_count(cpus: &[CpuUsage]) -> usize {
+    cpus.iter().filter(|cpu| cpu.name != "overall").count()
+}
+
+/// green while every cpu has headroom, yellow when nearly saturated and red once work is queueing
+fn load_color(load: f64, cores: usize) -> Color {
+    let per_core = load / cores.max(1) as f64;
+
+    if per_core < 0.7 {
+        Color::Green
+    } else if per_core < 1.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
 /// simple in-place right pad for Strings
 ///
 /// # Examples
@@ -874,3 +2638,64 @@ fn responsive_area(chunk: Rect) -> Rect {
         centered_rect(50, 50, chunk)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        proc::Proc,
+        source::{DataSource, FixtureSource},
+    };
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn render(state: &mut State) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(240, 50)).unwrap();
+        terminal.draw(|f| ui(f, state)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_render_fixture() {
+        let mut state = State::with_source(
+            false,
+            Box::new(FixtureSource::new(vec![Proc {
+                pid: 101,
+                name: Some(String::from("postgres")),
+                user: Some(String::from("alice")),
+                ..Default::default()
+            }])),
+        );
+
+        let screen = render(&mut state);
+
+        assert!(screen.contains("postgres"));
+        assert!(screen.contains("alice"));
+    }
+
+    #[test]
+    fn test_load_color_per_core() {
+        // a load of 1 saturates a single core
+        assert_eq!(load_color(0.69, 1), Color::Green);
+        assert_eq!(load_color(0.7, 1), Color::Yellow);
+        assert_eq!(load_color(1.0, 1), Color::Red);
+        assert_eq!(load_color(1.0, 2), Color::Green);
+        assert_eq!(load_color(1.0, 0), Color::Red);
+    }
+
+    #[test]
+    fn test_core_count_skips_overall() {
+        let mut source = FixtureSource::new(Vec::new());
+        assert_eq!(core_count(&source.read_cpus()), 4);
+        assert_eq!(
+            load_color(3.5, core_count(&source.read_cpus())),
+            Color::Yellow
+        );
+    }
+}
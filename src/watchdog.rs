@@ -0,0 +1,360 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    io,
+    process::{Command, Stdio},
+    thread,
+};
+
+use sysinfo::{Pid, Signal, System};
+
+use crate::{audit::AuditEntry, proc::Proc};
+
+/// how often a rule may act when it doesn't say `every`, in seconds
+pub const DEFAULT_ACTION_INTERVAL: u64 = 60;
+
+/// signals a rule can send, by the name `kill -l` uses
+const SIGNALS: [(&str, Signal); 9] = [
+    ("HUP", Signal::Hangup),
+    ("INT", Signal::Interrupt),
+    ("QUIT", Signal::Quit),
+    ("KILL", Signal::Kill),
+    ("USR1", Signal::User1),
+    ("USR2", Signal::User2),
+    ("TERM", Signal::Term),
+    ("CONT", Signal::Continue),
+    ("STOP", Signal::Stop),
+];
+
+/// what a rule does to a process once its alert fires
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Signal(Signal),
+    /// sets the nice value
+    Renice(i32),
+    /// shell command, `{pid}` and `{name}` are replaced with the process'
+    Run(String),
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Signal(signal) => write!(f, "signal {}", signal_name(*signal)),
+            Action::Renice(nice) => write!(f, "renice {}", nice),
+            Action::Run(command) => write!(f, "run {}", command),
+        }
+    }
+}
+
+/// parses the part of a rule after `do`, i.e. `signal TERM`, `renice 10` or `run <command>`
+pub fn parse_action(s: &str) -> Result<Action, String> {
+    let (kind, arg) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+    let arg = arg.trim();
+
+    match kind {
+        "signal" => {
+            let name = arg.trim_start_matches("SIG");
+            SIGNALS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, signal)| Action::Signal(*signal))
+                .ok_or_else(|| format!("unknown signal `{}`", arg))
+        }
+        "renice" => arg
+            .parse()
+            .ok()
+            .filter(|nice| (-20..=19).contains(nice))
+            .map(Action::Renice)
+            .ok_or_else(|| format!("bad nice value `{}`", arg)),
+        "run" if !arg.is_empty() => Ok(Action::Run(arg.to_string())),
+        _ => Err(format!(
+            "expected signal, renice or run, got `{}`",
+            s.trim()
+        )),
+    }
+}
+
+fn signal_name(signal: Signal) -> &'static str {
+    SIGNALS
+        .iter()
+        .find(|(_, s)| *s == signal)
+        .map_or("?", |(name, _)| name)
+}
+
+/// runs rule actions, with the guard rails that keep a bad rule from taking the box down
+#[derive(Default)]
+pub struct Watchdog {
+    /// actions only really happen when chadtop runs with `--watchdog`, before that they're all
+    /// dry runs
+    pub armed: bool,
+    /// `set dry-run` in the config, keeps it a dry run even when armed
+    pub dry_run: bool,
+    /// `set allow-users a b` in the config, only processes of these users are touched. When empty
+    /// only processes of the user running chadtop are
+    pub allow_users: Vec<String>,
+    /// (rule name, pid) -> last time the rule acted on that process
+    last_action: HashMap<(String, u32), u64>,
+}
+
+impl Watchdog {
+    pub fn new() -> Watchdog {
+        Watchdog::default()
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        !self.armed || self.dry_run
+    }
+
+    /// handles a `set <setting> <values...>` line of the config
+    pub fn set(&mut self, setting: &str) -> Result<(), String> {
+        let mut words = setting.split_whitespace();
+
+        match words.next() {
+            Some("dry-run") => self.dry_run = true,
+            Some("allow-users") => self
+                .allow_users
+                .extend(words.flat_map(|w| w.split(',')).map(str::to_string)),
+            other => return Err(format!("unknown setting `{}`", other.unwrap_or(""))),
+        }
+
+        Ok(())
+    }
+
+    /// whether chadtop may act on `proc` at all
+    fn allowed(&self, proc: &Proc, actor: &str) -> Result<(), String> {
+        if proc.pid == std::process::id() || proc.pid == 1 {
+            return Err(String::from("refused: never acts on chadtop or init"));
+        }
+
+        let user = proc.user.as_deref().unwrap_or("");
+        let allowed = if self.allow_users.is_empty() {
+            user == actor
+        } else {
+            self.allow_users.iter().any(|u| u == user)
+        };
+
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!(
+                "refused: user {} not allowed",
+                proc.user.as_deref().unwrap_or("n/a")
+            ))
+        }
+    }
+
+    /// runs `action` of the rule `rule` on `proc` unless a guard rail stops it, at most once per
+    /// `interval` seconds per rule and process. Returns what happened for the audit log
+    #[allow(clippy::too_many_arguments)]
+    pub fn act(
+        &mut self,
+        rule: &str,
+        action: &Action,
+        interval: u64,
+        proc: &Proc,
+        sys: &System,
+        actor: &str,
+        now: u64,
+    ) -> AuditEntry {
+        let mut entry = AuditEntry::new(
+            now,
+            actor,
+            &format!("watchdog {}", rule),
+            proc,
+            action.to_string(),
+        );
+
+        // entries past their interval don't limit anything anymore
+        self.last_action
+            .retain(|(r, _), last| r != rule || now.saturating_sub(*last) < interval);
+        let key = (rule.to_string(), proc.pid);
+        let last = self.last_action.get(&key).copied();
+        entry.result = if let Err(e) = self.allowed(proc, actor) {
+            e
+        } else if last.is_some_and(|last| now.saturating_sub(last) < interval) {
+            format!("rate limited: once every {}s", interval)
+        } else {
+            self.last_action.insert(key, now);
+            if self.is_dry_run() {
+                String::from("dry run")
+            } else {
+                match run_action(action, proc, sys) {
+                    Ok(()) => String::from("ok"),
+                    Err(e) => format!("failed: {}", e),
+                }
+            }
+        };
+
+        entry
+    }
+}
+
+fn run_action(action: &Action, proc: &Proc, sys: &System) -> io::Result<()> {
+    // the pid could have been reused since the refresh that fired the alert
+    let process = sys
+        .process(Pid::from_u32(proc.pid))
+        .filter(|p| p.start_time() == proc.start_time)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "process exited"))?;
+
+    match action {
+        Action::Signal(signal) => match process.kill_with(*signal) {
+            Some(true) => Ok(()),
+            Some(false) => Err(io::Error::last_os_error()),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "signal not supported",
+            )),
+        },
+        Action::Renice(nice) => renice(proc.pid, *nice),
+        Action::Run(command) => {
+            let command = command
+                .replace("{pid}", &proc.pid.to_string())
+                .replace("{name}", &shell_quote(proc.name.as_deref().unwrap_or("")));
+
+            let mut child = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()?;
+            thread::spawn(move || child.wait());
+            Ok(())
+        }
+    }
+}
+
+pub fn renice(pid: u32, nice: i32) -> io::Result<()> {
+    // SAFETY: setpriority only reads its arguments
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// wraps `s` in single quotes so process names can't inject into `run` commands
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate};
+
+    fn proc(pid: u32, user: &str) -> Proc {
+        Proc {
+            pid,
+            user: Some(String::from(user)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(
+            parse_action("signal TERM"),
+            Ok(Action::Signal(Signal::Term))
+        );
+        assert_eq!(
+            parse_action("signal SIGKILL"),
+            Ok(Action::Signal(Signal::Kill))
+        );
+        assert_eq!(parse_action("renice 10"), Ok(Action::Renice(10)));
+        assert_eq!(
+            parse_action("run logger {name} {pid}"),
+            Ok(Action::Run(String::from("logger {name} {pid}")))
+        );
+        assert!(parse_action("renice 40").is_err());
+        assert!(parse_action("signal FOO").is_err());
+        assert!(parse_action("run").is_err());
+        assert_eq!(Action::Signal(Signal::Term).to_string(), "signal TERM");
+    }
+
+    #[test]
+    fn test_guard_rails() {
+        let mut watchdog = Watchdog::new();
+        watchdog.set("allow-users ci,runner").unwrap();
+        let sys = System::new();
+        let action = Action::Signal(Signal::Term);
+
+        let other = watchdog.act("leak", &action, 60, &proc(42, "root"), &sys, "ci", 0);
+        let first = watchdog.act("leak", &action, 60, &proc(43, "runner"), &sys, "ci", 0);
+        let second = watchdog.act("leak", &action, 60, &proc(43, "runner"), &sys, "ci", 30);
+        let third = watchdog.act("leak", &action, 60, &proc(43, "runner"), &sys, "ci", 60);
+        let own = watchdog.act(
+            "leak",
+            &action,
+            60,
+            &proc(std::process::id(), "ci"),
+            &sys,
+            "ci",
+            999,
+        );
+
+        assert!(other.result.starts_with("refused"));
+        assert_eq!(first.result, "dry run");
+        assert!(second.result.starts_with("rate limited"));
+        assert_eq!(third.result, "dry run");
+        assert!(own.result.starts_with("refused"));
+        assert!(watchdog.set("bogus").is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_is_per_process() {
+        let mut watchdog = Watchdog::new();
+        let sys = System::new();
+        let action = Action::Signal(Signal::Term);
+
+        // two chrome instances leaked by the same test runner in one refresh
+        let first = watchdog.act("leak", &action, 60, &proc(43, "ci"), &sys, "ci", 0);
+        let second = watchdog.act("leak", &action, 60, &proc(44, "ci"), &sys, "ci", 0);
+        let again = watchdog.act("leak", &action, 60, &proc(44, "ci"), &sys, "ci", 10);
+
+        assert_eq!(first.result, "dry run");
+        assert_eq!(second.result, "dry run");
+        assert!(again.result.starts_with("rate limited"));
+    }
+
+    #[test]
+    fn test_armed_watchdog_signals_child() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+        let mut sys = System::new();
+        sys.refresh_processes_specifics(
+            ProcessesToUpdate::Some(&[Pid::from_u32(pid)]),
+            true,
+            ProcessRefreshKind::nothing(),
+        );
+        let target = Proc {
+            pid,
+            start_time: sys.process(Pid::from_u32(pid)).unwrap().start_time(),
+            user: Some(String::from("me")),
+            ..Default::default()
+        };
+
+        let mut watchdog = Watchdog::new();
+        watchdog.armed = true;
+        let entry = watchdog.act(
+            "t",
+            &Action::Signal(Signal::Kill),
+            0,
+            &target,
+            &sys,
+            "me",
+            0,
+        );
+        let status = child.wait().unwrap();
+
+        assert_eq!(entry.result, "ok");
+        assert!(!status.success());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a'; rm -rf /"), "'a'\\''; rm -rf /'");
+    }
+}